@@ -1,8 +1,11 @@
-use crate::lexer::Token;
+use crate::lexer::{PrimitiveKind, Token};
 
-use super::{literal::Literal, statement::BlockStatement, Identifier};
+use super::{
+    literal::Literal, pattern::MatchArm, signature, statement::BlockStatement, Identifier,
+    Parameter,
+};
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq)]
 pub enum Expression {
     Identifier(Identifier),
     Literal(Literal),
@@ -25,7 +28,8 @@ pub enum Expression {
     },
     FunctionLiteral {
         token: Token, // fn tok
-        parameters: Vec<Identifier>,
+        parameters: Vec<Parameter>,
+        return_kind: Option<PrimitiveKind>,
         body: Box<BlockStatement>,
     },
     FunctionCall {
@@ -39,17 +43,57 @@ pub enum Expression {
         index: Box<Expression>,
     },
     DotNotation {
-        token: Token, // .
+        token: Token, // . or ?.
         left: Box<Expression>,
         right: Box<Expression>,
+        /// `left?.right`, null instead of an error when `left` is null
+        optional: bool,
+    },
+    /// `match value do pattern -> result ... end`
+    Match {
+        token: Token, // match
+        value: Box<Expression>,
+        arms: Vec<MatchArm>,
+    },
+    /// `value is null` or `value is str`
+    Is {
+        token: Token, // is
+        value: Box<Expression>,
+        test: TypeTest,
+    },
+    SliceExpression {
+        token: Token, // [
+        left: Box<Expression>,
+        start: Option<Box<Expression>>,
+        end: Option<Box<Expression>>,
+        step: Option<Box<Expression>>,
     },
 }
 
+/// What the right side of `is` checks a value for
+#[derive(Clone, Debug, PartialEq)]
+pub enum TypeTest {
+    Null,
+    Kind(PrimitiveKind),
+}
+
+impl std::fmt::Display for TypeTest {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TypeTest::Null => write!(f, "null"),
+            TypeTest::Kind(kind) => write!(f, "{}", kind),
+        }
+    }
+}
+
 impl std::fmt::Display for Expression {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             Expression::Identifier(ident) => write!(f, "{}", ident.value),
             Expression::Literal(lit) => write!(f, "{}", lit),
+            Expression::Prefix {
+                operator, right, ..
+            } if operator == "not" => write!(f, "(not {})", right),
             Expression::Prefix {
                 operator, right, ..
             } => write!(f, "({}{})", operator, right),
@@ -72,17 +116,12 @@ impl std::fmt::Display for Expression {
                 Ok(())
             }
             Expression::FunctionLiteral {
-                parameters, body, ..
+                parameters,
+                return_kind,
+                body,
+                ..
             } => {
-                write!(f, "fn(")?;
-                for (i, param) in parameters.iter().enumerate() {
-                    if i == parameters.len() - 1 {
-                        write!(f, "{}", param)?;
-                    } else {
-                        write!(f, "{}, ", param)?;
-                    }
-                }
-                write!(f, ") {{{:?}}}", body)
+                write!(f, "fn{} {{{:?}}}", signature(parameters, return_kind), body)
             }
             Expression::FunctionCall {
                 function,
@@ -100,7 +139,43 @@ impl std::fmt::Display for Expression {
                 write!(f, ")")
             }
             Expression::IndexExpression { left, index, .. } => write!(f, "({}[{}])", left, index),
-            Expression::DotNotation { left, right, .. } => write!(f, "({}.{})", left, right),
+            Expression::DotNotation {
+                left,
+                right,
+                optional,
+                ..
+            } => {
+                let dot = if *optional { "?." } else { "." };
+                write!(f, "({}{}{})", left, dot, right)
+            }
+            Expression::Is { value, test, .. } => write!(f, "({} is {})", value, test),
+            Expression::Match { value, arms, .. } => {
+                write!(f, "match {} do", value)?;
+                for arm in arms {
+                    write!(f, " {},", arm)?;
+                }
+                write!(f, " end")
+            }
+            Expression::SliceExpression {
+                left,
+                start,
+                end,
+                step,
+                ..
+            } => {
+                write!(f, "({}[", left)?;
+                if let Some(start) = start {
+                    write!(f, "{}", start)?;
+                }
+                write!(f, ":")?;
+                if let Some(end) = end {
+                    write!(f, "{}", end)?;
+                }
+                if let Some(step) = step {
+                    write!(f, ":{}", step)?;
+                }
+                write!(f, "])")
+            }
         }
     }
 }
@@ -1,10 +1,13 @@
 use super::expression::Expression;
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq)]
 pub enum Literal {
     Integer(i64),
+    Float(f64),
     Boolean(bool),
+    Null,
     String(String),
+    Regex(String),
     Array(Vec<Expression>),
     Hash(Vec<(Expression, Expression)>),
 }
@@ -13,8 +16,11 @@ impl std::fmt::Display for Literal {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             Literal::Integer(int) => write!(f, "{}", int),
+            Literal::Float(float) => write!(f, "{:?}", float),
             Literal::Boolean(bool) => write!(f, "{}", bool),
+            Literal::Null => write!(f, "null"),
             Literal::String(string) => write!(f, "{}", string),
+            Literal::Regex(pattern) => write!(f, "/{}/", pattern),
             Literal::Array(array) => {
                 write!(f, "[")?;
                 for (i, expr) in array.iter().enumerate() {
@@ -1,11 +1,13 @@
-use crate::lexer::Token;
+use crate::lexer::{PrimitiveKind, Token};
 
 mod expression;
 mod literal;
+mod pattern;
 mod statement;
 
-pub use expression::Expression;
+pub use expression::{Expression, TypeTest};
 pub use literal::Literal;
+pub use pattern::{MatchArm, Pattern};
 pub use statement::{BlockStatement, Program, Statement};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -19,3 +21,29 @@ impl std::fmt::Display for Identifier {
         write!(f, "{}", &self.value)
     }
 }
+
+/// A function parameter and its optional type hint, `str name`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Parameter {
+    pub name: Identifier,
+    pub kind: Option<PrimitiveKind>,
+}
+
+impl std::fmt::Display for Parameter {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.kind {
+            Some(kind) => write!(f, "{} {}", kind, self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// The parameter list and return hint of a function, `(str name, n) -> str`
+pub fn signature(parameters: &[Parameter], return_kind: &Option<PrimitiveKind>) -> String {
+    let parameters: Vec<String> = parameters.iter().map(Parameter::to_string).collect();
+
+    match return_kind {
+        Some(kind) => format!("({}) -> {}", parameters.join(", "), kind),
+        None => format!("({})", parameters.join(", ")),
+    }
+}
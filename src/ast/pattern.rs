@@ -0,0 +1,86 @@
+use super::{expression::Expression, literal::Literal, Identifier};
+use crate::lexer::Token;
+
+/// The left side of a `match` arm, or of a destructuring `let`.
+#[derive(Clone, PartialEq)]
+pub enum Pattern {
+    /// `_`, matches anything
+    Wildcard,
+    /// A name, matches anything and binds it
+    Binding(Identifier),
+    /// A number, string, boolean or null equal to the value
+    Value(Literal),
+    /// `1 to 10`, a number between both ends inclusive
+    Range(Literal, Literal),
+    /// `[first, second, ...rest]`, `rest` is a binding or a wildcard
+    Array {
+        items: Vec<Pattern>,
+        rest: Option<Box<Pattern>>,
+    },
+    /// `{"code": 0, "stdout": out}` or `{name, age}`, other keys are ignored
+    Hash(Vec<(String, Pattern)>),
+}
+
+impl Pattern {
+    /// The names the pattern binds, in order.
+    pub fn bindings(&self) -> Vec<&Identifier> {
+        match self {
+            Pattern::Binding(name) => vec![name],
+            Pattern::Array { items, rest } => items
+                .iter()
+                .chain(rest.as_deref())
+                .flat_map(Pattern::bindings)
+                .collect(),
+            Pattern::Hash(entries) => entries
+                .iter()
+                .flat_map(|(_, pattern)| pattern.bindings())
+                .collect(),
+            Pattern::Wildcard | Pattern::Value(_) | Pattern::Range(..) => Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Pattern::Wildcard => write!(f, "_"),
+            Pattern::Binding(name) => write!(f, "{}", name),
+            Pattern::Value(Literal::String(string)) => write!(f, "{:?}", string),
+            Pattern::Value(literal) => write!(f, "{}", literal),
+            Pattern::Range(start, end) => write!(f, "{} to {}", start, end),
+            Pattern::Array { items, rest } => {
+                let mut parts: Vec<String> = items.iter().map(Pattern::to_string).collect();
+                if let Some(rest) = rest {
+                    parts.push(format!("...{}", rest));
+                }
+                write!(f, "[{}]", parts.join(", "))
+            }
+            Pattern::Hash(entries) => {
+                let parts: Vec<String> = entries
+                    .iter()
+                    .map(|(key, pattern)| format!("{:?}: {}", key, pattern))
+                    .collect();
+                write!(f, "{{{}}}", parts.join(", "))
+            }
+        }
+    }
+}
+
+/// `pattern if guard -> body` inside a `match`
+#[derive(Clone, PartialEq)]
+pub struct MatchArm {
+    pub token: Token, // ->
+    pub pattern: Pattern,
+    pub guard: Option<Expression>,
+    pub body: Expression,
+}
+
+impl std::fmt::Display for MatchArm {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.pattern)?;
+        if let Some(guard) = &self.guard {
+            write!(f, " if {}", guard)?;
+        }
+        write!(f, " -> {}", self.body)
+    }
+}
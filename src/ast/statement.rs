@@ -1,20 +1,35 @@
 use crate::lexer::{PrimitiveKind, Token};
 
-use super::{expression::Expression, Identifier};
+use super::{expression::Expression, pattern::Pattern, Identifier};
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq)]
 pub enum Statement {
     Let {
         token: Token,
         name: Identifier,
         value: Expression,
         value_kind: Option<PrimitiveKind>,
+        /// Declared with `const`, cannot be reassigned
+        constant: bool,
+    },
+    /// `let [a, b] = arr` or `let {name, age} = person`
+    Destructure {
+        token: Token,
+        pattern: Pattern,
+        value: Expression,
+        constant: bool,
     },
     ReAssign {
         token: Token,
         name: Identifier,
         value: Expression,
     },
+    /// Assignment to an element or field, `arr[0] = v` or `cfg.server.port = v`
+    Assign {
+        token: Token,
+        target: Expression,
+        value: Expression,
+    },
     Return {
         token: Token,
         value: Expression,
@@ -28,14 +43,32 @@ pub enum Statement {
 impl std::fmt::Debug for Statement {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            Statement::Let { name, value, .. } => {
-                write!(f, "let {} = {}) ", name, value)
+            Statement::Let {
+                name,
+                value,
+                constant,
+                ..
+            } => {
+                let keyword = if *constant { "const" } else { "let" };
+                write!(f, "{} {} = {}) ", keyword, name, value)
+            }
+            Statement::Destructure {
+                pattern,
+                value,
+                constant,
+                ..
+            } => {
+                let keyword = if *constant { "const" } else { "let" };
+                write!(f, "{} {} = {}", keyword, pattern, value)
             }
             Statement::Return { value, .. } => write!(f, "return {};", value),
             Statement::Expression { value, .. } => write!(f, "{}", value),
             Statement::ReAssign { name, value, .. } => {
                 write!(f, "reassign {} = {}", name, value)
             }
+            Statement::Assign { target, value, .. } => {
+                write!(f, "assign {} = {}", target, value)
+            }
         }
     }
 }
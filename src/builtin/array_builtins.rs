@@ -0,0 +1,559 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use super::Context;
+use crate::object::{HashKey, Object, Visited};
+
+pub(super) fn wrong_arguments(got: usize, expected: &str) -> Object {
+    Object::Error(format!(
+        "Wrong number of arguments. Got {}, expected {}",
+        got, expected
+    ))
+}
+
+fn array_argument(name: &str, arg: &Object) -> Result<Vec<Object>, Object> {
+    match arg {
+        Object::Array(items) => Ok(items.borrow().clone()),
+        _ => Err(Object::Error(format!(
+            "Argument to `{}` must be ARRAY, got {}",
+            name, arg
+        ))),
+    }
+}
+
+/// Calls a predicate callback, which has to answer with a boolean.
+fn predicate(ctx: &mut Context, function: &Object, item: &Object) -> Result<bool, Object> {
+    match ctx.call(function, vec![item.clone()]) {
+        Object::Boolean(bool) => Ok(bool),
+        err @ Object::Error(_) => Err(err),
+        other => Err(ctx.error(&format!(
+            "Callback to `{}` must return a Boolean, got {}",
+            ctx.name(),
+            other.type_name()
+        ))),
+    }
+}
+
+/// Resolves a possibly negative index against `len`, clamped to `0..=len`.
+pub(super) fn clamp_index(index: i64, len: usize) -> usize {
+    if index < 0 {
+        len.saturating_sub(index.unsigned_abs() as usize)
+    } else {
+        (index as usize).min(len)
+    }
+}
+
+pub fn last(_: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() != 1 {
+        return wrong_arguments(args.len(), "1");
+    }
+
+    match &args[0] {
+        Object::Array(items) => items.borrow().last().cloned().unwrap_or(Object::Null),
+        Object::String(string) => match string.chars().last() {
+            Some(c) => Object::String(c.to_string()),
+            None => Object::Null,
+        },
+        _ => Object::Error(format!("Argument to `last` must be ARRAY, got {}", args[0])),
+    }
+}
+
+pub fn rest(_: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() != 1 {
+        return wrong_arguments(args.len(), "1");
+    }
+
+    match array_argument("rest", &args[0]) {
+        Ok(items) if items.is_empty() => Object::Null,
+        Ok(items) => Object::array(items[1..].to_vec()),
+        Err(err) => err,
+    }
+}
+
+pub fn slice(_: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() < 2 || args.len() > 3 {
+        return wrong_arguments(args.len(), "2 or 3");
+    }
+
+    let (start, end) = match (&args[1], args.get(2)) {
+        (Object::Integer(start), None) => (*start, None),
+        (Object::Integer(start), Some(Object::Integer(end))) => (*start, Some(*end)),
+        _ => {
+            return Object::Error(format!(
+                "Bounds for `slice` must be integers, got {}",
+                args[1..]
+                    .iter()
+                    .map(|arg| arg.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" and ")
+            ))
+        }
+    };
+
+    match &args[0] {
+        Object::Array(items) => {
+            let items = items.borrow();
+            let start = clamp_index(start, items.len());
+            let end = end.map_or(items.len(), |end| clamp_index(end, items.len()));
+
+            Object::array(items[start..end.max(start)].to_vec())
+        }
+        Object::String(string) => {
+            let chars = string.chars().collect::<Vec<_>>();
+            let start = clamp_index(start, chars.len());
+            let end = end.map_or(chars.len(), |end| clamp_index(end, chars.len()));
+
+            Object::String(chars[start..end.max(start)].iter().collect())
+        }
+        _ => Object::Error(format!(
+            "Argument to `slice` must be ARRAY or STRING, got {}",
+            args[0]
+        )),
+    }
+}
+
+pub fn concat(_: &mut Context, args: Vec<Object>) -> Object {
+    let mut result = Vec::new();
+
+    for arg in &args {
+        match array_argument("concat", arg) {
+            Ok(items) => result.extend(items),
+            Err(err) => return err,
+        }
+    }
+
+    Object::array(result)
+}
+
+pub fn reverse(_: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() != 1 {
+        return wrong_arguments(args.len(), "1");
+    }
+
+    match &args[0] {
+        Object::Array(items) => Object::array(items.borrow().iter().rev().cloned().collect()),
+        Object::String(string) => Object::String(string.chars().rev().collect()),
+        _ => Object::Error(format!(
+            "Argument to `reverse` must be ARRAY or STRING, got {}",
+            args[0]
+        )),
+    }
+}
+
+pub fn contains(_: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() != 2 {
+        return wrong_arguments(args.len(), "2");
+    }
+
+    match array_argument("contains", &args[0]) {
+        Ok(items) => {
+            for item in &items {
+                match item.equals(&args[1]) {
+                    Ok(true) => return Object::Boolean(true),
+                    Ok(false) => {}
+                    Err(msg) => return Object::Error(format!("contains: {}", msg)),
+                }
+            }
+            Object::Boolean(false)
+        }
+        Err(err) => err,
+    }
+}
+
+pub fn index_of(_: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() != 2 {
+        return wrong_arguments(args.len(), "2");
+    }
+
+    match array_argument("indexOf", &args[0]) {
+        Ok(items) => {
+            for (i, item) in items.iter().enumerate() {
+                match item.equals(&args[1]) {
+                    Ok(true) => return Object::Integer(i as i64),
+                    Ok(false) => {}
+                    Err(msg) => return Object::Error(format!("indexOf: {}", msg)),
+                }
+            }
+            Object::Integer(-1)
+        }
+        Err(err) => err,
+    }
+}
+
+pub fn join(_: &mut Context, args: Vec<Object>) -> Object {
+    if args.is_empty() || args.len() > 2 {
+        return wrong_arguments(args.len(), "1 or 2");
+    }
+
+    let separator = match args.get(1) {
+        None => "",
+        Some(Object::String(separator)) => separator.as_str(),
+        Some(other) => {
+            return Object::Error(format!(
+                "Separator for `join` must be STRING, got {}",
+                other
+            ))
+        }
+    };
+
+    match array_argument("join", &args[0]) {
+        Ok(items) => Object::String(
+            items
+                .iter()
+                .map(|item| item.to_string())
+                .collect::<Vec<_>>()
+                .join(separator),
+        ),
+        Err(err) => err,
+    }
+}
+
+pub fn map(ctx: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() != 2 {
+        return wrong_arguments(args.len(), "2");
+    }
+
+    let items = match array_argument("map", &args[0]) {
+        Ok(items) => items,
+        Err(err) => return err,
+    };
+
+    let mut result = Vec::with_capacity(items.len());
+    for item in items {
+        let mapped = ctx.call(&args[1], vec![item]);
+        if mapped.is_error() {
+            return mapped;
+        }
+        result.push(mapped);
+    }
+
+    Object::array(result)
+}
+
+pub fn filter(ctx: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() != 2 {
+        return wrong_arguments(args.len(), "2");
+    }
+
+    let items = match array_argument("filter", &args[0]) {
+        Ok(items) => items,
+        Err(err) => return err,
+    };
+
+    let mut result = Vec::new();
+    for item in items {
+        match predicate(ctx, &args[1], &item) {
+            Ok(true) => result.push(item),
+            Ok(false) => {}
+            Err(err) => return err,
+        }
+    }
+
+    Object::array(result)
+}
+
+pub fn reduce(ctx: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() < 2 || args.len() > 3 {
+        return wrong_arguments(args.len(), "2 or 3");
+    }
+
+    let mut items = match array_argument("reduce", &args[0]) {
+        Ok(items) => items.into_iter(),
+        Err(err) => return err,
+    };
+
+    // Without an initial value the first element starts the fold
+    let mut acc = match args.get(2).cloned().or_else(|| items.next()) {
+        Some(acc) => acc,
+        None => {
+            return Object::Error("`reduce` of an empty array needs an initial value".to_string())
+        }
+    };
+
+    for item in items {
+        acc = ctx.call(&args[1], vec![acc, item]);
+        if acc.is_error() {
+            return acc;
+        }
+    }
+
+    acc
+}
+
+pub fn find(ctx: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() != 2 {
+        return wrong_arguments(args.len(), "2");
+    }
+
+    let items = match array_argument("find", &args[0]) {
+        Ok(items) => items,
+        Err(err) => return err,
+    };
+
+    for item in items {
+        match predicate(ctx, &args[1], &item) {
+            Ok(true) => return item,
+            Ok(false) => {}
+            Err(err) => return err,
+        }
+    }
+
+    Object::Null
+}
+
+pub fn any(ctx: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() != 2 {
+        return wrong_arguments(args.len(), "2");
+    }
+
+    let items = match array_argument("any", &args[0]) {
+        Ok(items) => items,
+        Err(err) => return err,
+    };
+
+    for item in items {
+        match predicate(ctx, &args[1], &item) {
+            Ok(true) => return Object::Boolean(true),
+            Ok(false) => {}
+            Err(err) => return err,
+        }
+    }
+
+    Object::Boolean(false)
+}
+
+pub fn all(ctx: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() != 2 {
+        return wrong_arguments(args.len(), "2");
+    }
+
+    let items = match array_argument("all", &args[0]) {
+        Ok(items) => items,
+        Err(err) => return err,
+    };
+
+    for item in items {
+        match predicate(ctx, &args[1], &item) {
+            Ok(true) => {}
+            Ok(false) => return Object::Boolean(false),
+            Err(err) => return err,
+        }
+    }
+
+    Object::Boolean(true)
+}
+
+pub fn sort_by(ctx: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() != 2 {
+        return wrong_arguments(args.len(), "2");
+    }
+
+    let items = match array_argument("sortBy", &args[0]) {
+        Ok(items) => items,
+        Err(err) => return err,
+    };
+
+    // Compute every key once up front, the sort itself never calls back
+    let mut keyed = Vec::with_capacity(items.len());
+    for item in items {
+        let key = ctx.call(&args[1], vec![item.clone()]);
+        if key.is_error() {
+            return key;
+        }
+        keyed.push((key, item));
+    }
+
+    let mut error = None;
+    keyed.sort_by(|(a, _), (b, _)| {
+        a.compare(b).unwrap_or_else(|msg| {
+            error.get_or_insert(msg);
+            Ordering::Equal
+        })
+    });
+
+    match error {
+        Some(msg) => Object::Error(format!("sortBy: {}", msg)),
+        None => Object::array(keyed.into_iter().map(|(_, item)| item).collect()),
+    }
+}
+
+/// `sort(arr)`, a sorted copy in the order `<` uses
+pub fn sort(_: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() != 1 {
+        return wrong_arguments(args.len(), "1");
+    }
+
+    let mut items = match array_argument("sort", &args[0]) {
+        Ok(items) => items,
+        Err(err) => return err,
+    };
+
+    let mut error = None;
+    items.sort_by(|a, b| {
+        a.compare(b).unwrap_or_else(|msg| {
+            error.get_or_insert(msg);
+            Ordering::Equal
+        })
+    });
+
+    match error {
+        Some(msg) => Object::Error(format!("sort: {}", msg)),
+        None => Object::array(items),
+    }
+}
+
+pub fn zip(_: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() != 2 {
+        return wrong_arguments(args.len(), "2");
+    }
+
+    match (
+        array_argument("zip", &args[0]),
+        array_argument("zip", &args[1]),
+    ) {
+        (Ok(left), Ok(right)) => Object::array(
+            left.into_iter()
+                .zip(right)
+                .map(|(a, b)| Object::array(vec![a, b]))
+                .collect(),
+        ),
+        (Err(err), _) | (_, Err(err)) => err,
+    }
+}
+
+fn flatten_into(
+    result: &mut Vec<Object>,
+    items: &[Object],
+    depth: i64,
+    visited: &mut Visited,
+) -> Result<(), Object> {
+    for item in items {
+        match item {
+            Object::Array(inner) if depth > 0 => {
+                if !visited.enter(inner) {
+                    return Err(Object::Error(
+                        "flatten: Cannot flatten a value that contains itself".to_string(),
+                    ));
+                }
+                flatten_into(result, &inner.borrow(), depth - 1, visited)?;
+                visited.leave(inner);
+            }
+            _ => result.push(item.clone()),
+        }
+    }
+
+    Ok(())
+}
+
+pub fn flatten(_: &mut Context, args: Vec<Object>) -> Object {
+    if args.is_empty() || args.len() > 2 {
+        return wrong_arguments(args.len(), "1 or 2");
+    }
+
+    let depth = match args.get(1) {
+        None => 1,
+        Some(Object::Integer(depth)) if *depth >= 0 => *depth,
+        Some(other) => {
+            return Object::Error(format!(
+                "Depth for `flatten` must be a positive integer, got {}",
+                other
+            ))
+        }
+    };
+
+    match array_argument("flatten", &args[0]) {
+        Ok(items) => {
+            let mut result = Vec::new();
+            let mut visited = Visited::default();
+            if let Object::Array(outer) = &args[0] {
+                visited.enter(outer);
+            }
+
+            match flatten_into(&mut result, &items, depth, &mut visited) {
+                Ok(()) => Object::array(result),
+                Err(err) => err,
+            }
+        }
+        Err(err) => err,
+    }
+}
+
+pub fn unique(_: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() != 1 {
+        return wrong_arguments(args.len(), "1");
+    }
+
+    let items = match array_argument("unique", &args[0]) {
+        Ok(items) => items,
+        Err(err) => return err,
+    };
+
+    // Strings and booleans are checked in constant time. Numbers and
+    // collections are compared like `==`, so `1` and `1.0` are duplicates
+    let mut seen = HashSet::new();
+    let mut result: Vec<Object> = Vec::new();
+
+    for item in items {
+        let is_new = match HashKey::from_object(&item) {
+            Some(key @ (HashKey::String(_) | HashKey::Boolean(_))) => seen.insert(key),
+            _ => {
+                let mut is_new = true;
+                for kept in &result {
+                    match kept.equals(&item) {
+                        Ok(true) => {
+                            is_new = false;
+                            break;
+                        }
+                        Ok(false) => {}
+                        Err(msg) => return Object::Error(format!("unique: {}", msg)),
+                    }
+                }
+                is_new
+            }
+        };
+
+        if is_new {
+            result.push(item);
+        }
+    }
+
+    Object::array(result)
+}
+
+pub fn range(_: &mut Context, args: Vec<Object>) -> Object {
+    let bounds = args
+        .iter()
+        .map(|arg| match arg {
+            Object::Integer(int) => Ok(*int),
+            _ => Err(Object::Error(format!(
+                "Arguments to `range` must be integers, got {}",
+                arg
+            ))),
+        })
+        .collect::<Result<Vec<_>, _>>();
+
+    let (start, end, step) = match bounds.as_deref() {
+        Ok([end]) => (0, *end, 1),
+        Ok([start, end]) => (*start, *end, 1),
+        Ok([start, end, step]) => (*start, *end, *step),
+        Ok(_) => return wrong_arguments(args.len(), "1 to 3"),
+        Err(err) => return err.clone(),
+    };
+
+    if step == 0 {
+        return Object::Error("Step for `range` must not be zero".to_string());
+    }
+
+    let mut result = Vec::new();
+    let mut current = start;
+
+    while (step > 0 && current < end) || (step < 0 && current > end) {
+        result.push(Object::Integer(current));
+        current = match current.checked_add(step) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+
+    Object::array(result)
+}
@@ -0,0 +1,107 @@
+use super::Context;
+use crate::object::{HashKey, Object};
+
+fn env_value(value: &Object) -> Option<String> {
+    match value {
+        Object::String(value) => Some(value.clone()),
+        Object::Integer(_) | Object::Boolean(_) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+pub fn get_env(_: &mut Context, args: Vec<Object>) -> Object {
+    if args.is_empty() || args.len() > 2 {
+        return Object::Error(format!(
+            "Wrong number of arguments. Got {}, expected 1 or 2",
+            args.len()
+        ));
+    }
+
+    let name = match &args[0] {
+        Object::String(name) => name,
+        _ => {
+            return Object::Error(format!(
+                "Argument to `getEnv` must be STRING, got {}",
+                args[0]
+            ))
+        }
+    };
+
+    match std::env::var(name) {
+        Ok(value) => Object::String(value),
+        Err(_) => args.get(1).cloned().unwrap_or(Object::Null),
+    }
+}
+
+pub fn set_env(_: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() != 2 {
+        return Object::Error(format!(
+            "Wrong number of arguments. Got {}, expected 2",
+            args.len()
+        ));
+    }
+
+    let (name, value) = match (&args[0], env_value(&args[1])) {
+        (Object::String(name), Some(value)) => (name, value),
+        _ => return Object::Error(format!(
+            "Arguments to `setEnv` must be STRING and STRING, INTEGER or BOOLEAN, got {} and {}",
+            args[0], args[1]
+        )),
+    };
+
+    if name.is_empty() || name.contains('=') || name.contains('\0') || value.contains('\0') {
+        return Object::Error(format!("Invalid environment variable: {}", name));
+    }
+
+    // Processes started by exec, pipe and spawn inherit this
+    std::env::set_var(name, value);
+    Object::Empty
+}
+
+pub fn unset_env(_: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() != 1 {
+        return Object::Error(format!(
+            "Wrong number of arguments. Got {}, expected 1",
+            args.len()
+        ));
+    }
+
+    match &args[0] {
+        Object::String(name) if name.is_empty() || name.contains('=') || name.contains('\0') => {
+            Object::Error(format!("Invalid environment variable: {}", name))
+        }
+        Object::String(name) => {
+            std::env::remove_var(name);
+            Object::Empty
+        }
+        _ => Object::Error(format!(
+            "Argument to `unsetEnv` must be a variable name, got {}",
+            args[0]
+        )),
+    }
+}
+
+pub fn env_map(_: &mut Context, args: Vec<Object>) -> Object {
+    if !args.is_empty() {
+        return Object::Error(format!(
+            "Wrong number of arguments. Got {}, expected 0",
+            args.len()
+        ));
+    }
+
+    let mut vars = std::env::vars_os()
+        .map(|(name, value)| {
+            (
+                name.to_string_lossy().into_owned(),
+                value.to_string_lossy().into_owned(),
+            )
+        })
+        .collect::<Vec<_>>();
+    vars.sort();
+
+    Object::hash(
+        vars.into_iter()
+            .map(|(name, value)| (HashKey::String(name), Object::String(value)))
+            .collect(),
+    )
+}
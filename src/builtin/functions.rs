@@ -1,168 +1,261 @@
+use super::{
+    array_builtins, env_builtins, json_builtins, process_builtins, regex_builtins, toml_builtins,
+    type_builtins, yaml_builtins,
+};
+use super::{Builtin, Context, NativeModule};
 use crate::object::Object;
 use std::collections::HashMap;
 
+fn define(
+    map: &mut HashMap<String, Object>,
+    name: &str,
+    func: impl Fn(&mut Context, Vec<Object>) -> Object + 'static,
+) {
+    map.insert(
+        name.to_string(),
+        Object::BuiltinFunction(Builtin::new(name, func)),
+    );
+}
+
+fn define_module(map: &mut HashMap<String, Object>, module: NativeModule) {
+    map.insert(module.name().to_string(), module.into_object());
+}
+
 pub fn builtins() -> HashMap<String, Object> {
     let mut map = HashMap::new();
 
-    map.insert(
-        "len".to_string(),
-        Object::BuiltinFunction(|args| {
-            if args.len() != 1 {
-                return Object::Error(format!(
-                    "Wrong number of arguments. Got {}, expected 1",
-                    args.len()
-                ));
-            }
+    define(&mut map, "len", |_, args| {
+        if args.len() != 1 {
+            return Object::Error(format!(
+                "Wrong number of arguments. Got {}, expected 1",
+                args.len()
+            ));
+        }
 
-            match &args[0] {
-                Object::String(string) => Object::Integer(string.len() as i64),
-                Object::Array(array) => Object::Integer(array.len() as i64),
-                _ => Object::Error(format!("Argument to `len` not supported, got {}", args[0])),
-            }
-        }),
-    );
+        match &args[0] {
+            Object::String(string) => Object::Integer(string.chars().count() as i64),
+            Object::Array(array) => Object::Integer(array.borrow().len() as i64),
+            Object::Hash(hash) => Object::Integer(hash.borrow().len() as i64),
+            _ => Object::Error(format!(
+                "Argument to `len` not supported, got {}",
+                args[0].type_name()
+            )),
+        }
+    });
 
-    map.insert(
-        "first".to_string(),
-        Object::BuiltinFunction(|args| {
-            if args.len() != 1 {
-                return Object::Error(format!(
-                    "Wrong number of arguments. Got {}, expected 1",
-                    args.len()
-                ));
-            }
+    define(&mut map, "first", |_, args| {
+        if args.len() != 1 {
+            return Object::Error(format!(
+                "Wrong number of arguments. Got {}, expected 1",
+                args.len()
+            ));
+        }
 
-            match &args[0] {
-                Object::Array(array) => {
-                    if !array.is_empty() {
-                        return array[0].clone();
-                    }
-                    Object::Null
-                }
-                Object::String(string) => {
-                    if !string.is_empty() {
-                        return Object::String(string.chars().take(1).collect());
-                    }
-                    Object::Null
+        match &args[0] {
+            Object::Array(array) => array.borrow().first().cloned().unwrap_or(Object::Null),
+            Object::String(string) => {
+                if !string.is_empty() {
+                    return Object::String(string.chars().take(1).collect());
                 }
-                _ => Object::Error(format!(
-                    "Argument to `first` must be ARRAY, got {}",
-                    args[0]
-                )),
+                Object::Null
             }
-        }),
-    );
+            _ => Object::Error(format!(
+                "Argument to `first` must be ARRAY, got {}",
+                args[0]
+            )),
+        }
+    });
 
-    map.insert(
-        "println".to_string(),
-        Object::BuiltinFunction(|args| {
-            println!(
-                "{}",
-                args.iter()
-                    .map(|arg| format!("{} ", arg))
-                    .collect::<String>()
+    define(&mut map, "println", |_, args| {
+        println!(
+            "{}",
+            args.iter()
+                .map(|arg| format!("{} ", arg))
+                .collect::<String>()
+        );
+        Object::Empty
+    });
+
+    define(&mut map, "fprintln", |_, args| {
+        if args.is_empty() {
+            return Object::Error(
+                "fprintln requires at least one argument (format string)".to_string(),
             );
-            Object::Empty
-        }),
-    );
+        }
 
-    map.insert(
-        "fprintln".to_string(),
-        Object::BuiltinFunction(|args| {
-            if args.is_empty() {
-                return Object::Error(
-                    "fprintln requires at least one argument (format string)".to_string(),
-                );
-            }
+        let format_string = match &args[0] {
+            Object::String(s) => s,
+            _ => return Object::Error("First argument to fprintln must be a string".to_string()),
+        };
 
-            let format_string = match &args[0] {
-                Object::String(s) => s,
-                _ => {
-                    return Object::Error("First argument to fprintln must be a string".to_string())
-                }
-            };
-
-            let mut result = String::new();
-            let mut arg_index = 1;
-            let mut chars = format_string.chars().peekable();
-
-            while let Some(ch) = chars.next() {
-                if ch == '{' {
-                    if chars.peek() == Some(&'}') {
-                        chars.next(); // consume the closing '}'
-                        if arg_index < args.len() {
-                            result.push_str(&args[arg_index].to_string());
-                            arg_index += 1;
-                        } else {
-                            return Object::Error(
-                                "Not enough arguments provided for format string".to_string(),
-                            );
-                        }
-                    } else {
-                        result.push(ch);
-                    }
-                } else if ch == '}' {
-                    if chars.peek() == Some(&'}') {
-                        chars.next(); // consume the second '}'
-                        result.push('}');
+        let mut result = String::new();
+        let mut arg_index = 1;
+        let mut chars = format_string.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            if ch == '{' {
+                if chars.peek() == Some(&'}') {
+                    chars.next(); // consume the closing '}'
+                    if arg_index < args.len() {
+                        result.push_str(&args[arg_index].to_string());
+                        arg_index += 1;
                     } else {
-                        return Object::Error("Invalid format string: unmatched '}'".to_string());
+                        return Object::Error(
+                            "Not enough arguments provided for format string".to_string(),
+                        );
                     }
                 } else {
                     result.push(ch);
                 }
+            } else if ch == '}' {
+                if chars.peek() == Some(&'}') {
+                    chars.next(); // consume the second '}'
+                    result.push('}');
+                } else {
+                    return Object::Error("Invalid format string: unmatched '}'".to_string());
+                }
+            } else {
+                result.push(ch);
             }
+        }
 
-            if arg_index < args.len() {
-                return Object::Error("Too many arguments provided for format string".to_string());
-            }
+        if arg_index < args.len() {
+            return Object::Error("Too many arguments provided for format string".to_string());
+        }
 
-            println!("{}", result);
-            Object::Empty
-        }),
-    );
+        println!("{}", result);
+        Object::Empty
+    });
 
-    map.insert(
-        "push".to_string(),
-        Object::BuiltinFunction(|args| {
-            if args.len() != 2 {
-                return Object::Error(format!(
-                    "Wrong number of arguments. Got {}, expected 2",
-                    args.len()
-                ));
-            }
+    define(&mut map, "push", |_, args| {
+        if args.len() != 2 {
+            return Object::Error(format!(
+                "Wrong number of arguments. Got {}, expected 2",
+                args.len()
+            ));
+        }
 
-            match &args[0] {
-                Object::Array(array) => {
-                    let mut new_array = array.clone();
-                    new_array.push(args[1].clone());
-                    Object::Array(new_array)
-                }
-                _ => Object::Error(format!("Argument to `push` must be ARRAY, got {}", args[0])),
+        match &args[0] {
+            Object::Array(array) => {
+                let mut new_array = array.borrow().clone();
+                new_array.push(args[1].clone());
+                Object::array(new_array)
             }
-        }),
-    );
+            _ => Object::Error(format!("Argument to `push` must be ARRAY, got {}", args[0])),
+        }
+    });
 
-    map.insert(
-        "pop".to_string(),
-        Object::BuiltinFunction(|args| {
-            if args.len() != 1 {
-                return Object::Error(format!(
-                    "Wrong number of arguments. Got {}, expected 1",
-                    args.len()
-                ));
-            }
+    define(&mut map, "pop", |_, args| {
+        if args.len() != 1 {
+            return Object::Error(format!(
+                "Wrong number of arguments. Got {}, expected 1",
+                args.len()
+            ));
+        }
 
-            match &args[0] {
-                Object::Array(array) => {
-                    let mut new_array = array.clone();
-                    new_array.pop();
-                    Object::Array(new_array)
-                }
-                _ => Object::Error(format!("Argument to `push` must be ARRAY, got {}", args[0])),
+        match &args[0] {
+            Object::Array(array) => {
+                let mut new_array = array.borrow().clone();
+                new_array.pop();
+                Object::array(new_array)
             }
-        }),
-    );
+            _ => Object::Error(format!("Argument to `push` must be ARRAY, got {}", args[0])),
+        }
+    });
+
+    define(&mut map, "last", array_builtins::last);
+
+    define(&mut map, "rest", array_builtins::rest);
+
+    define(&mut map, "slice", array_builtins::slice);
+
+    define(&mut map, "concat", array_builtins::concat);
+
+    define(&mut map, "reverse", array_builtins::reverse);
+
+    define(&mut map, "contains", array_builtins::contains);
+
+    define(&mut map, "indexOf", array_builtins::index_of);
+
+    define(&mut map, "join", array_builtins::join);
+
+    define(&mut map, "map", array_builtins::map);
+
+    define(&mut map, "filter", array_builtins::filter);
+
+    define(&mut map, "reduce", array_builtins::reduce);
+
+    define(&mut map, "find", array_builtins::find);
+
+    define(&mut map, "any", array_builtins::any);
+
+    define(&mut map, "all", array_builtins::all);
+
+    define(&mut map, "sort", array_builtins::sort);
+
+    define(&mut map, "sortBy", array_builtins::sort_by);
+
+    define(&mut map, "zip", array_builtins::zip);
+
+    define(&mut map, "flatten", array_builtins::flatten);
+
+    define(&mut map, "unique", array_builtins::unique);
+
+    define(&mut map, "range", array_builtins::range);
+
+    define(&mut map, "copy", |_, args| {
+        if args.len() != 1 {
+            return Object::Error(format!(
+                "Wrong number of arguments. Got {}, expected 1",
+                args.len()
+            ));
+        }
+
+        args[0].shallow_copy()
+    });
+
+    define(&mut map, "deepCopy", |_, args| {
+        if args.len() != 1 {
+            return Object::Error(format!(
+                "Wrong number of arguments. Got {}, expected 1",
+                args.len()
+            ));
+        }
+
+        args[0].deep_copy()
+    });
+
+    define(&mut map, "type", type_builtins::type_of);
+
+    define(&mut map, "int", type_builtins::to_int);
+
+    define(&mut map, "float", type_builtins::to_float);
+
+    define(&mut map, "str", type_builtins::to_str);
+
+    define(&mut map, "bool", type_builtins::to_bool);
+
+    define(&mut map, "exec", process_builtins::exec);
+
+    define(&mut map, "pipe", process_builtins::pipe);
+
+    define(&mut map, "spawn", process_builtins::spawn);
+
+    define(&mut map, "getEnv", env_builtins::get_env);
+
+    define(&mut map, "setEnv", env_builtins::set_env);
+
+    define(&mut map, "unsetEnv", env_builtins::unset_env);
+
+    define(&mut map, "env", env_builtins::env_map);
+
+    define(&mut map, "envMap", env_builtins::env_map);
+
+    define(&mut map, "regex", regex_builtins::regex);
+
+    define_module(&mut map, json_builtins::json_module());
+    define_module(&mut map, toml_builtins::toml_module());
+    define_module(&mut map, yaml_builtins::yaml_module());
 
     map
 }
@@ -0,0 +1,486 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+use super::{Context, NativeModule};
+use crate::object::{HashKey, Object, OrderedHash, Visited};
+
+/// How many arrays and objects may be open at once. Parsing recurses, so
+/// without a limit hostile input could overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+struct JsonParser<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    col: usize,
+    depth: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn new(src: &'a str) -> Self {
+        Self {
+            chars: src.chars().peekable(),
+            line: 1,
+            col: 1,
+            depth: 0,
+        }
+    }
+
+    fn error(&self, msg: &str) -> String {
+        format!(
+            "JSON parse error at line {}, column {}: {}",
+            self.line, self.col, msg
+        )
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+
+        if c == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.peek() {
+            self.advance();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.advance();
+                Ok(())
+            }
+            Some(c) => Err(self.error(&format!("expected '{}', found '{}'", expected, c))),
+            None => Err(self.error(&format!("expected '{}', found end of input", expected))),
+        }
+    }
+
+    fn parse_document(&mut self) -> Result<Object, String> {
+        let value = self.parse_value()?;
+        self.skip_whitespace();
+
+        match self.peek() {
+            None => Ok(value),
+            Some(c) => Err(self.error(&format!("unexpected '{}' after value", c))),
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Object, String> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some('{' | '[') if self.depth == MAX_DEPTH => {
+                Err(self.error(&format!("nested deeper than {} levels", MAX_DEPTH)))
+            }
+            Some(c @ ('{' | '[')) => {
+                self.depth += 1;
+                let value = if c == '{' {
+                    self.parse_object()
+                } else {
+                    self.parse_array()
+                };
+                self.depth -= 1;
+                value
+            }
+            Some('"') => Ok(Object::String(self.parse_string()?)),
+            Some('-' | '0'..='9') => self.parse_number(),
+            Some('t') => self.parse_keyword("true", Object::Boolean(true)),
+            Some('f') => self.parse_keyword("false", Object::Boolean(false)),
+            Some('n') => self.parse_keyword("null", Object::Null),
+            Some(c) => Err(self.error(&format!("unexpected '{}'", c))),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn parse_keyword(&mut self, keyword: &str, value: Object) -> Result<Object, String> {
+        for expected in keyword.chars() {
+            if self.peek() != Some(expected) {
+                return Err(self.error(&format!("invalid literal, expected `{}`", keyword)));
+            }
+            self.advance();
+        }
+
+        Ok(value)
+    }
+
+    fn parse_object(&mut self) -> Result<Object, String> {
+        self.expect('{')?;
+        let mut pairs = OrderedHash::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.advance();
+            return Ok(Object::hash(pairs));
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.error("expected string key"));
+            }
+
+            let key = HashKey::String(self.parse_string()?);
+
+            self.skip_whitespace();
+            self.expect(':')?;
+
+            let value = self.parse_value()?;
+
+            // Later duplicates win, like every JSON.parse out there
+            pairs.insert(key, value);
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => {
+                    self.advance();
+                }
+                Some('}') => {
+                    self.advance();
+                    return Ok(Object::hash(pairs));
+                }
+                _ => return Err(self.error("expected ',' or '}' in object")),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Object, String> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.advance();
+            return Ok(Object::array(items));
+        }
+
+        loop {
+            items.push(self.parse_value()?);
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => {
+                    self.advance();
+                }
+                Some(']') => {
+                    self.advance();
+                    return Ok(Object::array(items));
+                }
+                _ => return Err(self.error("expected ',' or ']' in array")),
+            }
+        }
+    }
+
+    fn parse_hex_escape(&mut self) -> Result<u32, String> {
+        let mut code = 0;
+
+        for _ in 0..4 {
+            let digit = match self.peek().and_then(|c| c.to_digit(16)) {
+                Some(digit) => digit,
+                None => return Err(self.error("invalid \\u escape")),
+            };
+            self.advance();
+            code = code * 16 + digit;
+        }
+
+        Ok(code)
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut string = String::new();
+
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some('"') => {
+                    self.advance();
+                    return Ok(string);
+                }
+                Some('\\') => {
+                    self.advance();
+                    let escaped = match self.peek() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            self.advance();
+                            let mut code = self.parse_hex_escape()?;
+
+                            // Characters outside the BMP arrive as a surrogate pair
+                            if (0xD800..0xDC00).contains(&code) {
+                                if self.advance() != Some('\\') || self.advance() != Some('u') {
+                                    return Err(self.error("unpaired surrogate in \\u escape"));
+                                }
+                                let low = self.parse_hex_escape()?;
+                                if !(0xDC00..0xE000).contains(&low) {
+                                    return Err(self.error("unpaired surrogate in \\u escape"));
+                                }
+                                code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                            }
+
+                            match char::from_u32(code) {
+                                Some(c) => string.push(c),
+                                None => return Err(self.error("invalid \\u escape")),
+                            }
+                            continue;
+                        }
+                        _ => return Err(self.error("invalid escape sequence")),
+                    };
+                    self.advance();
+                    string.push(escaped);
+                }
+                Some(c) if (c as u32) < 0x20 => {
+                    return Err(self.error("control character in string"))
+                }
+                Some(c) => {
+                    self.advance();
+                    string.push(c);
+                }
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Object, String> {
+        let mut number = String::new();
+        let mut is_float = false;
+
+        if self.peek() == Some('-') {
+            number.push('-');
+            self.advance();
+        }
+
+        match self.peek() {
+            Some('0') => {
+                number.push('0');
+                self.advance();
+            }
+            Some('1'..='9') => self.take_digits(&mut number),
+            _ => return Err(self.error("expected digit")),
+        }
+
+        if self.peek() == Some('.') {
+            is_float = true;
+            number.push('.');
+            self.advance();
+            if !matches!(self.peek(), Some('0'..='9')) {
+                return Err(self.error("expected digit after decimal point"));
+            }
+            self.take_digits(&mut number);
+        }
+
+        if let Some('e' | 'E') = self.peek() {
+            is_float = true;
+            number.push('e');
+            self.advance();
+            if let Some(sign @ ('+' | '-')) = self.peek() {
+                number.push(sign);
+                self.advance();
+            }
+            if !matches!(self.peek(), Some('0'..='9')) {
+                return Err(self.error("expected digit in exponent"));
+            }
+            self.take_digits(&mut number);
+        }
+
+        if !is_float {
+            if let Ok(int) = number.parse::<i64>() {
+                return Ok(Object::Integer(int));
+            }
+        }
+
+        match number.parse::<f64>() {
+            Ok(float) => Ok(Object::Float(float)),
+            Err(_) => Err(self.error(&format!("invalid number {}", number))),
+        }
+    }
+
+    fn take_digits(&mut self, number: &mut String) {
+        while let Some(c @ '0'..='9') = self.peek() {
+            number.push(c);
+            self.advance();
+        }
+    }
+}
+
+pub fn parse_json(src: &str) -> Result<Object, String> {
+    JsonParser::new(src).parse_document()
+}
+
+fn write_json_string(out: &mut String, string: &str) {
+    out.push('"');
+
+    for c in string.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+}
+
+fn write_newline(out: &mut String, indent: &str, depth: usize) {
+    if !indent.is_empty() {
+        out.push('\n');
+        for _ in 0..depth {
+            out.push_str(indent);
+        }
+    }
+}
+
+fn contains_itself() -> String {
+    "Cannot serialize a value that contains itself to JSON".to_string()
+}
+
+fn write_json(
+    out: &mut String,
+    obj: &Object,
+    indent: &str,
+    depth: usize,
+    visited: &mut Visited,
+) -> Result<(), String> {
+    match obj {
+        Object::Null => out.push_str("null"),
+        Object::Boolean(bool) => out.push_str(&bool.to_string()),
+        Object::Integer(int) => out.push_str(&int.to_string()),
+        Object::Float(float) if float.is_finite() => out.push_str(&format!("{:?}", float)),
+        Object::Float(float) => return Err(format!("Cannot serialize {} to JSON", float)),
+        Object::String(string) => write_json_string(out, string),
+        Object::Array(array) => {
+            let items = array.borrow();
+            if items.is_empty() {
+                out.push_str("[]");
+                return Ok(());
+            }
+            if !visited.enter(array) {
+                return Err(contains_itself());
+            }
+
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_newline(out, indent, depth + 1);
+                write_json(out, item, indent, depth + 1, visited)?;
+            }
+            write_newline(out, indent, depth);
+            out.push(']');
+            visited.leave(array);
+        }
+        Object::Hash(hash) => {
+            let pairs = hash.borrow();
+            if pairs.is_empty() {
+                out.push_str("{}");
+                return Ok(());
+            }
+            if !visited.enter(hash) {
+                return Err(contains_itself());
+            }
+
+            out.push('{');
+            for (i, (key, value)) in pairs.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_newline(out, indent, depth + 1);
+
+                write_json_string(out, &key.to_string());
+
+                out.push(':');
+                if !indent.is_empty() {
+                    out.push(' ');
+                }
+                write_json(out, value, indent, depth + 1, visited)?;
+            }
+            write_newline(out, indent, depth);
+            out.push('}');
+            visited.leave(hash);
+        }
+        _ => {
+            return Err(format!(
+                "Cannot serialize {} to JSON",
+                obj.type_name().to_lowercase()
+            ))
+        }
+    }
+
+    Ok(())
+}
+
+pub fn stringify_json(obj: &Object, indent: &str) -> Result<String, String> {
+    let mut out = String::new();
+    write_json(&mut out, obj, indent, 0, &mut Visited::default())?;
+    Ok(out)
+}
+
+pub fn json_parse(_: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() != 1 {
+        return Object::Error(format!(
+            "Wrong number of arguments. Got {}, expected 1",
+            args.len()
+        ));
+    }
+
+    match &args[0] {
+        Object::String(src) => parse_json(src).unwrap_or_else(Object::Error),
+        _ => Object::Error(format!(
+            "Argument to `json.parse` must be STRING, got {}",
+            args[0]
+        )),
+    }
+}
+
+pub fn json_stringify(_: &mut Context, args: Vec<Object>) -> Object {
+    if args.is_empty() || args.len() > 2 {
+        return Object::Error(format!(
+            "Wrong number of arguments. Got {}, expected 1 or 2",
+            args.len()
+        ));
+    }
+
+    let indent = match args.get(1) {
+        None => String::new(),
+        Some(Object::Integer(spaces)) if *spaces >= 0 => " ".repeat(*spaces as usize),
+        Some(Object::String(indent)) => indent.clone(),
+        Some(other) => {
+            return Object::Error(format!(
+                "Indent for `json.stringify` must be a number of spaces or a STRING, got {}",
+                other
+            ))
+        }
+    };
+
+    match stringify_json(&args[0], &indent) {
+        Ok(json) => Object::String(json),
+        Err(msg) => Object::Error(msg),
+    }
+}
+
+/// The `json` namespace, so scripts can call `json.parse(...)`.
+pub fn json_module() -> NativeModule {
+    NativeModule::new("json")
+        .function("parse", json_parse)
+        .function("stringify", json_stringify)
+}
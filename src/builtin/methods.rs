@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use super::array_builtins::wrong_arguments;
+use super::{process_builtins, regex_builtins, string_builtins, Builtin, Context};
+use crate::object::{HashKey, Object};
+
+/// Builtins reached with dot notation, looked up by the type name of the
+/// receiver. Both kinds get the receiver as their first argument: properties
+/// (`arr.length`) nothing else, methods (`arr.map(f)`) the call's arguments.
+#[derive(Default)]
+pub struct MethodRegistry {
+    properties: HashMap<String, HashMap<String, Builtin>>,
+    methods: HashMap<String, HashMap<String, Builtin>>,
+}
+
+impl MethodRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn define_property(&mut self, type_name: &str, name: &str, builtin: Builtin) {
+        self.properties
+            .entry(type_name.to_string())
+            .or_default()
+            .insert(name.to_string(), builtin);
+    }
+
+    pub fn define_method(&mut self, type_name: &str, name: &str, builtin: Builtin) {
+        self.methods
+            .entry(type_name.to_string())
+            .or_default()
+            .insert(name.to_string(), builtin);
+    }
+
+    pub fn property(&self, type_name: &str, name: &str) -> Option<&Builtin> {
+        self.properties.get(type_name)?.get(name)
+    }
+
+    pub fn method(&self, type_name: &str, name: &str) -> Option<&Builtin> {
+        self.methods.get(type_name)?.get(name)
+    }
+}
+
+/// The array builtins that double as methods, `map(arr, f)` is `arr.map(f)`.
+const ARRAY_METHODS: [&str; 22] = [
+    "push", "pop", "last", "rest", "slice", "concat", "reverse", "contains", "indexOf", "join",
+    "map", "filter", "reduce", "find", "any", "all", "sort", "sortBy", "zip", "flatten", "unique",
+    "copy",
+];
+
+pub fn methods(builtins: &HashMap<String, Object>) -> MethodRegistry {
+    let mut registry = MethodRegistry::new();
+
+    for name in ARRAY_METHODS {
+        if let Some(Object::BuiltinFunction(builtin)) = builtins.get(name) {
+            registry.define_method("Array", name, builtin.clone());
+        }
+    }
+
+    if let Some(Object::BuiltinFunction(len)) = builtins.get("len") {
+        registry.define_property("Array", "length", len.clone());
+    }
+
+    registry.define_method("Hash", "keys", Builtin::new("keys", hash_keys));
+    registry.define_method("Hash", "values", Builtin::new("values", hash_values));
+    registry.define_method("Hash", "has", Builtin::new("has", hash_has));
+    registry.define_method("Hash", "remove", Builtin::new("remove", hash_remove));
+
+    for type_name in ["Integer", "Float"] {
+        registry.define_method(type_name, "abs", Builtin::new("abs", number_abs));
+        registry.define_method(type_name, "toString", Builtin::new("toString", to_string));
+    }
+
+    string_builtins::define_methods(&mut registry);
+    process_builtins::define_methods(&mut registry);
+    regex_builtins::define_methods(&mut registry);
+
+    registry
+}
+
+fn hash_key(key: &Object) -> Result<HashKey, Object> {
+    HashKey::from_object(key)
+        .ok_or_else(|| Object::Error(format!("Unusable as hash key: {}", key.type_name())))
+}
+
+fn hash_keys(_: &mut Context, args: Vec<Object>) -> Object {
+    match &args[..] {
+        [Object::Hash(hash)] => {
+            Object::array(hash.borrow().keys().map(HashKey::to_object).collect())
+        }
+        _ => wrong_arguments(args.len().saturating_sub(1), "0"),
+    }
+}
+
+fn hash_values(_: &mut Context, args: Vec<Object>) -> Object {
+    match &args[..] {
+        [Object::Hash(hash)] => Object::array(hash.borrow().values().cloned().collect()),
+        _ => wrong_arguments(args.len().saturating_sub(1), "0"),
+    }
+}
+
+fn hash_has(_: &mut Context, args: Vec<Object>) -> Object {
+    match &args[..] {
+        [Object::Hash(hash), key] => match hash_key(key) {
+            Ok(key) => Object::Boolean(hash.borrow().contains_key(&key)),
+            Err(err) => err,
+        },
+        _ => wrong_arguments(args.len().saturating_sub(1), "1"),
+    }
+}
+
+/// Removes the key in place and returns its value, or null if it was missing.
+fn hash_remove(_: &mut Context, args: Vec<Object>) -> Object {
+    match &args[..] {
+        [Object::Hash(hash), key] => match hash_key(key) {
+            Ok(key) => hash.borrow_mut().remove(&key).unwrap_or(Object::Null),
+            Err(err) => err,
+        },
+        _ => wrong_arguments(args.len().saturating_sub(1), "1"),
+    }
+}
+
+fn number_abs(_: &mut Context, args: Vec<Object>) -> Object {
+    match &args[..] {
+        [Object::Integer(int)] => match int.checked_abs() {
+            Some(abs) => Object::Integer(abs),
+            None => Object::Error(format!("Integer overflow in `abs` of {}", int)),
+        },
+        [Object::Float(float)] => Object::Float(float.abs()),
+        _ => wrong_arguments(args.len().saturating_sub(1), "0"),
+    }
+}
+
+fn to_string(_: &mut Context, args: Vec<Object>) -> Object {
+    match &args[..] {
+        [receiver] => Object::String(receiver.to_string()),
+        _ => wrong_arguments(args.len().saturating_sub(1), "0"),
+    }
+}
@@ -1,5 +1,17 @@
+mod array_builtins;
+mod env_builtins;
 mod functions;
+mod json_builtins;
+mod methods;
+mod native;
+mod process_builtins;
+mod regex_builtins;
 mod string_builtins;
+mod toml_builtins;
+mod type_builtins;
+mod yaml_builtins;
 
 pub use functions::builtins;
-pub use string_builtins::{dot_str_builtins, DotBuiltinKind};
+pub use methods::{methods, MethodRegistry};
+pub use native::{Builtin, Context, NativeFn, NativeModule};
+pub use process_builtins::ProcessHandle;
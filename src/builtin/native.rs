@@ -0,0 +1,137 @@
+use std::rc::Rc;
+
+use crate::eval::Evaluator;
+use crate::lexer::Position;
+use crate::object::{Object, OrderedHash};
+
+/// Signature shared by every builtin, from `len` to functions registered by
+/// programs embedding the interpreter.
+pub type NativeFn = dyn Fn(&mut Context, Vec<Object>) -> Object;
+
+/// A named function implemented in Rust. Cloning is cheap, the function
+/// itself (and anything it captured) is shared.
+#[derive(Clone)]
+pub struct Builtin {
+    name: Rc<str>,
+    func: Rc<NativeFn>,
+}
+
+impl Builtin {
+    pub fn new(name: &str, func: impl Fn(&mut Context, Vec<Object>) -> Object + 'static) -> Self {
+        Self {
+            name: Rc::from(name),
+            func: Rc::new(func),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        position: Option<Position>,
+        args: Vec<Object>,
+    ) -> Object {
+        let mut ctx = Context {
+            evaluator,
+            name: &self.name,
+            position,
+        };
+
+        (self.func)(&mut ctx, args)
+    }
+}
+
+impl PartialEq for Builtin {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.func, &other.func)
+    }
+}
+
+impl std::fmt::Debug for Builtin {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "builtin `{}`", self.name)
+    }
+}
+
+/// What a builtin gets to see of the interpreter while it runs.
+pub struct Context<'a> {
+    evaluator: &'a mut Evaluator,
+    name: &'a str,
+    position: Option<Position>,
+}
+
+impl Context<'_> {
+    /// The name the running builtin was registered under, e.g. `json.parse`.
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    /// Calls a Tammr function or another builtin, e.g. a callback argument.
+    pub fn call(&mut self, function: &Object, args: Vec<Object>) -> Object {
+        match function {
+            Object::BuiltinFunction(builtin) => {
+                builtin.call(self.evaluator, self.position.clone(), args)
+            }
+            _ => self
+                .evaluator
+                .apply_function(function.clone(), args)
+                .unwrap_or(Object::Null),
+        }
+    }
+
+    /// Looks up a variable as the calling code would see it.
+    pub fn get_var(&self, name: &str) -> Option<Object> {
+        self.evaluator.env.borrow().get(name)
+    }
+
+    /// An error pointing at the line of the call.
+    pub fn error(&self, msg: &str) -> Object {
+        match &self.position {
+            Some(position) => Object::Error(format!("{} at line {}", msg, position.line + 1)),
+            None => Object::Error(msg.to_string()),
+        }
+    }
+}
+
+/// A namespace of builtins, like `json` or `toml`, that scripts reach
+/// through dot notation (`json.parse(text)`).
+pub struct NativeModule {
+    name: String,
+    members: OrderedHash,
+}
+
+impl NativeModule {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            members: OrderedHash::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn function(
+        mut self,
+        name: &str,
+        func: impl Fn(&mut Context, Vec<Object>) -> Object + 'static,
+    ) -> Self {
+        let builtin = Builtin::new(&format!("{}.{}", self.name, name), func);
+        self.members
+            .insert(name.into(), Object::BuiltinFunction(builtin));
+        self
+    }
+
+    pub fn value(mut self, name: &str, value: Object) -> Self {
+        self.members.insert(name.into(), value);
+        self
+    }
+
+    pub fn into_object(self) -> Object {
+        Object::hash(self.members)
+    }
+}
@@ -0,0 +1,544 @@
+use std::cell::RefCell;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, ChildStdout, Command, ExitStatus, Stdio};
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::array_builtins::wrong_arguments;
+use super::{Builtin, Context, MethodRegistry};
+use crate::object::{HashKey, Object, OrderedHash};
+
+/// How long to keep collecting output from a killed process before giving up.
+/// Grandchildren can hold the pipes open long after the child itself is gone.
+const KILL_GRACE: Duration = Duration::from_millis(100);
+
+#[derive(Default)]
+pub(crate) struct ExecOptions {
+    pub(crate) cwd: Option<String>,
+    pub(crate) env: Vec<(String, String)>,
+    pub(crate) stdin: Option<String>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) check: bool,
+}
+
+impl ExecOptions {
+    pub(crate) fn from_object(options: &Object) -> Result<Self, String> {
+        let pairs = match options {
+            Object::Hash(pairs) => pairs.borrow(),
+            _ => return Err(format!("Options must be a HASH, got {}", options)),
+        };
+
+        let mut result = ExecOptions::default();
+
+        for (key, value) in pairs.iter() {
+            let key = match key {
+                HashKey::String(key) => key.as_str(),
+                _ => return Err(format!("Option names must be strings, got {}", key)),
+            };
+
+            match (key, value) {
+                ("cwd", Object::String(cwd)) => result.cwd = Some(cwd.clone()),
+                ("env", Object::Hash(vars)) => {
+                    for (name, value) in vars.borrow().iter() {
+                        match (name, value) {
+                            (HashKey::String(name), Object::String(value)) => {
+                                result.env.push((name.clone(), value.clone()))
+                            }
+                            (HashKey::String(name), Object::Integer(value)) => {
+                                result.env.push((name.clone(), value.to_string()))
+                            }
+                            _ => {
+                                return Err(format!(
+                                    "Environment entries must be strings, got {}: {}",
+                                    name, value
+                                ))
+                            }
+                        }
+                    }
+                }
+                ("stdin", Object::String(input)) => result.stdin = Some(input.clone()),
+                ("timeout", Object::Integer(ms)) if *ms > 0 => {
+                    result.timeout = Some(Duration::from_millis(*ms as u64))
+                }
+                ("check", Object::Boolean(check)) => result.check = *check,
+                ("cwd" | "stdin", _) => {
+                    return Err(format!("Option `{}` must be a string, got {}", key, value))
+                }
+                ("env", _) => return Err(format!("Option `env` must be a HASH, got {}", value)),
+                ("timeout", _) => {
+                    return Err(format!(
+                        "Option `timeout` must be a positive number of milliseconds, got {}",
+                        value
+                    ))
+                }
+                ("check", _) => {
+                    return Err(format!("Option `check` must be a boolean, got {}", value))
+                }
+                _ => return Err(format!("Unknown option `{}`", key)),
+            }
+        }
+
+        Ok(result)
+    }
+
+    pub(crate) fn apply(&self, command: &mut Command) {
+        if let Some(cwd) = &self.cwd {
+            command.current_dir(cwd);
+        }
+
+        for (name, value) in &self.env {
+            command.env(name, value);
+        }
+    }
+}
+
+pub(crate) struct CapturedOutput {
+    pub(crate) stdout: String,
+    pub(crate) stderr: String,
+    pub(crate) code: Option<i32>,
+}
+
+impl CapturedOutput {
+    pub(crate) fn into_object(self) -> Object {
+        Object::hash(OrderedHash::from_iter([
+            ("stdout".into(), Object::String(self.stdout)),
+            ("stderr".into(), Object::String(self.stderr)),
+            (
+                "code".into(),
+                match self.code {
+                    Some(code) => Object::Integer(code as i64),
+                    None => Object::Null,
+                },
+            ),
+        ]))
+    }
+
+    /// Builds the error raised for a command that ran but did not exit cleanly,
+    /// carrying everything it printed so scripts can report it.
+    pub(crate) fn into_error(self, name: &str) -> Object {
+        let code = match self.code {
+            Some(code) => code.to_string(),
+            None => "none (killed by signal)".to_string(),
+        };
+
+        Object::Error(format!(
+            "`{}` exited with code {}\nstdout: {}\nstderr: {}",
+            name,
+            code,
+            self.stdout.trim_end(),
+            self.stderr.trim_end()
+        ))
+    }
+}
+
+/// Turns a command object into a `Command`. Strings go through the shell,
+/// arrays are used as argv directly so no quoting is needed.
+pub(crate) fn build_command(cmd: &Object) -> Result<Command, String> {
+    match cmd {
+        Object::String(line) => {
+            let mut command = if cfg!(windows) {
+                let mut command = Command::new("cmd");
+                command.arg("/C");
+                command
+            } else {
+                let mut command = Command::new("sh");
+                command.arg("-c");
+                command
+            };
+            command.arg(line);
+            Ok(command)
+        }
+        Object::Array(argv) => {
+            let argv = argv
+                .borrow()
+                .iter()
+                .map(|arg| match arg {
+                    Object::String(arg) => Ok(arg.clone()),
+                    Object::Integer(arg) => Ok(arg.to_string()),
+                    _ => Err(format!("Command arguments must be strings, got {}", arg)),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let (program, rest) = match argv.split_first() {
+                Some(split) => split,
+                None => return Err("Command array must not be empty".to_string()),
+            };
+
+            let mut command = Command::new(program);
+            command.args(rest);
+            Ok(command)
+        }
+        _ => Err(format!(
+            "Command must be a STRING or ARRAY of strings, got {}",
+            cmd
+        )),
+    }
+}
+
+pub(crate) fn describe_command(cmd: &Object) -> String {
+    match cmd {
+        Object::Array(argv) => argv
+            .borrow()
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => cmd.to_string(),
+    }
+}
+
+pub(crate) fn read_in_background<R: Read + Send + 'static>(reader: Option<R>) -> Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut reader) = reader {
+            let _ = reader.read_to_end(&mut buffer);
+        }
+        let _ = sender.send(String::from_utf8_lossy(&buffer).into_owned());
+    });
+
+    receiver
+}
+
+pub(crate) fn write_in_background(child: &mut Child, input: Option<&String>) {
+    if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+        let input = input.clone();
+        thread::spawn(move || {
+            let _ = stdin.write_all(input.as_bytes());
+        });
+    }
+}
+
+/// Waits for the child to exit. Returns `None` if the deadline passed first,
+/// leaving the child running so the caller can decide what to kill.
+fn wait_until(child: &mut Child, deadline: Option<Instant>) -> Result<Option<ExitStatus>, String> {
+    let deadline = match deadline {
+        Some(deadline) => deadline,
+        None => return child.wait().map(Some).map_err(|e| e.to_string()),
+    };
+
+    loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            return Ok(Some(status));
+        }
+
+        if Instant::now() >= deadline {
+            return Ok(None);
+        }
+
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+/// A running command or pipeline. Every stage's stdout feeds the next
+/// stage's stdin; the last stage's stdout is what scripts read.
+pub struct ProcessHandle {
+    name: String,
+    children: Vec<Child>,
+    stdout: Option<BufReader<ChildStdout>>,
+    stderr: Vec<Receiver<String>>,
+    timeout: Option<Duration>,
+    check: bool,
+    started: Instant,
+    killed: bool,
+    result: Option<Object>,
+}
+
+impl ProcessHandle {
+    pub(crate) fn spawn(commands: &[Object], options: ExecOptions) -> Result<Self, String> {
+        if commands.is_empty() {
+            return Err("Expected at least one command".to_string());
+        }
+
+        let name = commands
+            .iter()
+            .map(describe_command)
+            .collect::<Vec<_>>()
+            .join(" | ");
+
+        let mut children: Vec<Child> = Vec::new();
+        let mut stderr = Vec::new();
+        let mut previous_stdout: Option<ChildStdout> = None;
+
+        for cmd in commands {
+            let mut command = build_command(cmd)?;
+            options.apply(&mut command);
+
+            match previous_stdout.take() {
+                Some(stdout) => {
+                    command.stdin(Stdio::from(stdout));
+                }
+                None if options.stdin.is_some() => {
+                    command.stdin(Stdio::piped());
+                }
+                None => {}
+            }
+
+            let spawned = command
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn();
+
+            let mut child = match spawned {
+                Ok(child) => child,
+                Err(e) => {
+                    for child in &mut children {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                    }
+                    return Err(format!(
+                        "`{}` could not be started: {}",
+                        describe_command(cmd),
+                        e
+                    ));
+                }
+            };
+
+            if children.is_empty() {
+                write_in_background(&mut child, options.stdin.as_ref());
+            }
+
+            stderr.push(read_in_background(child.stderr.take()));
+            previous_stdout = child.stdout.take();
+            children.push(child);
+        }
+
+        Ok(Self {
+            name,
+            children,
+            stdout: previous_stdout.map(BufReader::new),
+            stderr,
+            timeout: options.timeout,
+            check: options.check,
+            started: Instant::now(),
+            killed: false,
+            result: None,
+        })
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.children.last().map(Child::id).unwrap_or_default()
+    }
+
+    /// Reads the next line of stdout without its line ending. Returns `None`
+    /// once the output is exhausted.
+    pub fn read_line(&mut self) -> Option<String> {
+        let reader = self.stdout.as_mut()?;
+        let mut line = String::new();
+
+        match reader.read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => {
+                if line.ends_with('\n') {
+                    line.pop();
+                    if line.ends_with('\r') {
+                        line.pop();
+                    }
+                }
+                Some(line)
+            }
+        }
+    }
+
+    pub fn kill(&mut self) {
+        for child in &mut self.children {
+            let _ = child.kill();
+        }
+        self.killed = true;
+    }
+
+    /// Waits for every stage to finish and returns the same hash `exec` does.
+    /// Output already consumed through `read_line` is not included again.
+    pub fn wait(&mut self) -> Object {
+        if let Some(result) = &self.result {
+            return result.clone();
+        }
+
+        let stdout = read_in_background(self.stdout.take());
+        let deadline = self.timeout.map(|timeout| self.started + timeout);
+        let mut code = None;
+        let mut timed_out = false;
+
+        for child in &mut self.children {
+            match wait_until(child, deadline) {
+                Ok(Some(status)) => code = status.code(),
+                Ok(None) => {
+                    timed_out = true;
+                    break;
+                }
+                Err(msg) => return Object::Error(format!("`{}` {}", self.name, msg)),
+            }
+        }
+
+        if timed_out {
+            for child in &mut self.children {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+        }
+
+        let abandoned = timed_out || self.killed;
+        let collect = |receiver: &Receiver<String>| {
+            if abandoned {
+                receiver.recv_timeout(KILL_GRACE).unwrap_or_default()
+            } else {
+                receiver.recv().unwrap_or_default()
+            }
+        };
+
+        let output = CapturedOutput {
+            stdout: collect(&stdout),
+            stderr: self.stderr.iter().map(collect).collect(),
+            code,
+        };
+
+        let result = if timed_out {
+            Object::Error(format!(
+                "`{}` timed out after {}ms\nstdout: {}\nstderr: {}",
+                self.name,
+                self.timeout.unwrap_or_default().as_millis(),
+                output.stdout.trim_end(),
+                output.stderr.trim_end()
+            ))
+        } else if self.check && output.code != Some(0) {
+            output.into_error(&self.name)
+        } else {
+            output.into_object()
+        };
+
+        self.result = Some(result.clone());
+        result
+    }
+}
+
+/// A handle dropped before `wait` would leave its children running, or as
+/// zombies once they exit, so they are killed and reaped here.
+impl Drop for ProcessHandle {
+    fn drop(&mut self) {
+        for child in &mut self.children {
+            if let Ok(None) = child.try_wait() {
+                let _ = child.kill();
+            }
+            let _ = child.wait();
+        }
+    }
+}
+
+impl std::fmt::Debug for ProcessHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "process `{}` (pid {})", self.name, self.pid())
+    }
+}
+
+/// Splits builtin arguments into commands and an optional trailing options hash.
+fn commands_and_options(name: &str, args: &[Object]) -> Result<(Vec<Object>, ExecOptions), Object> {
+    let (commands, options) = match args.split_last() {
+        Some((options @ Object::Hash(_), commands)) => (commands, Some(options)),
+        _ => (args, None),
+    };
+
+    let options = match options {
+        Some(options) => ExecOptions::from_object(options)
+            .map_err(|msg| Object::Error(format!("{}: {}", name, msg)))?,
+        None => ExecOptions::default(),
+    };
+
+    Ok((commands.to_vec(), options))
+}
+
+fn start(name: &str, args: &[Object]) -> Result<ProcessHandle, Object> {
+    let (commands, options) = commands_and_options(name, args)?;
+
+    ProcessHandle::spawn(&commands, options)
+        .map_err(|msg| Object::Error(format!("{}: {}", name, msg)))
+}
+
+pub fn exec(_: &mut Context, args: Vec<Object>) -> Object {
+    if args.is_empty() || args.len() > 2 {
+        return Object::Error(format!(
+            "Wrong number of arguments. Got {}, expected 1 or 2",
+            args.len()
+        ));
+    }
+
+    if let Some(options) = args.get(1) {
+        if !matches!(options, Object::Hash(_)) {
+            return Object::Error(format!("exec: Options must be a HASH, got {}", options));
+        }
+    }
+
+    match start("exec", &args) {
+        Ok(mut handle) => handle.wait(),
+        Err(err) => err,
+    }
+}
+
+pub fn pipe(_: &mut Context, args: Vec<Object>) -> Object {
+    match start("pipe", &args) {
+        Ok(mut handle) => handle.wait(),
+        Err(err) => err,
+    }
+}
+
+pub fn spawn(_: &mut Context, args: Vec<Object>) -> Object {
+    match start("spawn", &args) {
+        Ok(handle) => Object::Process(Rc::new(RefCell::new(handle))),
+        Err(err) => err,
+    }
+}
+
+pub fn define_methods(registry: &mut MethodRegistry) {
+    registry.define_property("Process", "pid", Builtin::new("pid", process_pid));
+    registry.define_method("Process", "pid", Builtin::new("pid", process_pid));
+    registry.define_method("Process", "wait", Builtin::new("wait", process_wait));
+    registry.define_method("Process", "kill", Builtin::new("kill", process_kill));
+    registry.define_method("Process", "lines", Builtin::new("lines", process_lines));
+}
+
+fn process_pid(_: &mut Context, args: Vec<Object>) -> Object {
+    match &args[..] {
+        [Object::Process(handle)] => Object::Integer(handle.borrow().pid() as i64),
+        _ => wrong_arguments(args.len().saturating_sub(1), "0"),
+    }
+}
+
+fn process_wait(_: &mut Context, args: Vec<Object>) -> Object {
+    match &args[..] {
+        [Object::Process(handle)] => handle.borrow_mut().wait(),
+        _ => wrong_arguments(args.len().saturating_sub(1), "0"),
+    }
+}
+
+fn process_kill(_: &mut Context, args: Vec<Object>) -> Object {
+    match &args[..] {
+        [Object::Process(handle)] => {
+            handle.borrow_mut().kill();
+            Object::Empty
+        }
+        _ => wrong_arguments(args.len().saturating_sub(1), "0"),
+    }
+}
+
+/// Calls the callback with every line of stdout as it arrives, then waits.
+fn process_lines(ctx: &mut Context, args: Vec<Object>) -> Object {
+    let [Object::Process(handle), callback] = &args[..] else {
+        return wrong_arguments(args.len().saturating_sub(1), "1");
+    };
+
+    // The borrow has to end before the callback runs, it may use the handle
+    loop {
+        let line = handle.borrow_mut().read_line();
+        let Some(line) = line else { break };
+
+        let result = ctx.call(callback, vec![Object::String(line)]);
+        if result.is_error() {
+            handle.borrow_mut().kill();
+            return result;
+        }
+    }
+
+    handle.borrow_mut().wait()
+}
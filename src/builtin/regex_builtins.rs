@@ -0,0 +1,195 @@
+use std::rc::Rc;
+
+use regex::{Captures, Regex};
+
+use super::array_builtins::wrong_arguments;
+use super::{Builtin, Context, MethodRegistry};
+use crate::object::{HashKey, Object, OrderedHash};
+
+pub fn regex(_: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() != 1 {
+        return wrong_arguments(args.len(), "1");
+    }
+
+    match &args[0] {
+        Object::String(pattern) => match Regex::new(pattern) {
+            Ok(regex) => Object::Regex(Rc::new(regex)),
+            Err(err) => Object::Error(format!("Invalid regex `{}`: {}", pattern, err)),
+        },
+        other => Object::Error(format!("Argument to `regex` must be STRING, got {}", other)),
+    }
+}
+
+pub fn define_methods(registry: &mut MethodRegistry) {
+    registry.define_method("Regex", "test", Builtin::new("test", regex_test));
+    registry.define_method("Regex", "match", Builtin::new("match", regex_match));
+    registry.define_method(
+        "Regex",
+        "captures",
+        Builtin::new("captures", regex_captures),
+    );
+    registry.define_method("Regex", "findAll", Builtin::new("findAll", regex_find_all));
+    registry.define_method("Regex", "replace", Builtin::new("replace", regex_replace));
+    registry.define_method("Regex", "split", Builtin::new("split", regex_split));
+    registry.define_property("Regex", "pattern", Builtin::new("pattern", regex_pattern));
+}
+
+/// The regex a method was called on and the text it should look at.
+fn subject<'a>(
+    name: &str,
+    args: &'a [Object],
+    arity: usize,
+) -> Result<(&'a Regex, &'a str), Object> {
+    if args.len() != arity + 1 {
+        return Err(wrong_arguments(
+            args.len().saturating_sub(1),
+            &arity.to_string(),
+        ));
+    }
+
+    match (&args[0], &args[1]) {
+        (Object::Regex(regex), Object::String(text)) => Ok((regex, text)),
+        (_, other) => Err(Object::Error(format!(
+            "Argument to `{}` must be STRING, got {}",
+            name, other
+        ))),
+    }
+}
+
+/// Numbered groups under their index (0 is the whole match), named groups
+/// under their name. Groups that did not take part in the match are null.
+fn captures_to_hash(regex: &Regex, captures: &Captures) -> Object {
+    let mut hash = OrderedHash::new();
+
+    for (i, name) in regex.capture_names().enumerate() {
+        let value = captures.get(i).map_or(Object::Null, |group| {
+            Object::String(group.as_str().to_string())
+        });
+
+        let key = match name {
+            Some(name) => HashKey::from(name),
+            None => HashKey::Integer(i as i64),
+        };
+        hash.insert(key, value);
+    }
+
+    Object::hash(hash)
+}
+
+fn regex_test(_: &mut Context, args: Vec<Object>) -> Object {
+    match subject("test", &args, 1) {
+        Ok((regex, text)) => Object::Boolean(regex.is_match(text)),
+        Err(err) => err,
+    }
+}
+
+fn regex_match(_: &mut Context, args: Vec<Object>) -> Object {
+    match subject("match", &args, 1) {
+        Ok((regex, text)) => regex.find(text).map_or(Object::Null, |found| {
+            Object::String(found.as_str().to_string())
+        }),
+        Err(err) => err,
+    }
+}
+
+fn regex_captures(_: &mut Context, args: Vec<Object>) -> Object {
+    match subject("captures", &args, 1) {
+        Ok((regex, text)) => regex
+            .captures(text)
+            .map_or(Object::Null, |captures| captures_to_hash(regex, &captures)),
+        Err(err) => err,
+    }
+}
+
+/// Every match as a string, or as a captures hash when the pattern has groups.
+fn regex_find_all(_: &mut Context, args: Vec<Object>) -> Object {
+    let (regex, text) = match subject("findAll", &args, 1) {
+        Ok(subject) => subject,
+        Err(err) => return err,
+    };
+
+    let matches = if regex.captures_len() > 1 {
+        regex
+            .captures_iter(text)
+            .map(|captures| captures_to_hash(regex, &captures))
+            .collect()
+    } else {
+        regex
+            .find_iter(text)
+            .map(|found| Object::String(found.as_str().to_string()))
+            .collect()
+    };
+
+    Object::array(matches)
+}
+
+/// Replaces every match. The replacement is either a string, where `$1` and
+/// `$name` refer to groups, or a function called with the captures hash.
+fn regex_replace(ctx: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() != 3 {
+        return wrong_arguments(args.len().saturating_sub(1), "2");
+    }
+
+    let (regex, text) = match subject("replace", &args[..2], 1) {
+        Ok(subject) => subject,
+        Err(err) => return err,
+    };
+
+    match &args[2] {
+        Object::String(replacement) => {
+            Object::String(regex.replace_all(text, replacement.as_str()).into_owned())
+        }
+        function @ (Object::Function { .. } | Object::BuiltinFunction(_)) => {
+            let mut result = String::new();
+            let mut last = 0;
+
+            for captures in regex.captures_iter(text) {
+                let Some(whole) = captures.get(0) else {
+                    continue;
+                };
+
+                match ctx.call(function, vec![captures_to_hash(regex, &captures)]) {
+                    Object::String(replacement) => {
+                        result.push_str(&text[last..whole.start()]);
+                        result.push_str(&replacement);
+                    }
+                    err @ Object::Error(_) => return err,
+                    other => {
+                        return ctx.error(&format!(
+                            "Callback to `replace` must return a String, got {}",
+                            other.type_name()
+                        ))
+                    }
+                }
+
+                last = whole.end();
+            }
+
+            result.push_str(&text[last..]);
+            Object::String(result)
+        }
+        other => Object::Error(format!(
+            "Replacement for `replace` must be STRING or FUNCTION, got {}",
+            other
+        )),
+    }
+}
+
+fn regex_split(_: &mut Context, args: Vec<Object>) -> Object {
+    match subject("split", &args, 1) {
+        Ok((regex, text)) => Object::array(
+            regex
+                .split(text)
+                .map(|part| Object::String(part.to_string()))
+                .collect(),
+        ),
+        Err(err) => err,
+    }
+}
+
+fn regex_pattern(_: &mut Context, args: Vec<Object>) -> Object {
+    match &args[..] {
+        [Object::Regex(regex)] => Object::String(regex.as_str().to_string()),
+        _ => wrong_arguments(args.len().saturating_sub(1), "0"),
+    }
+}
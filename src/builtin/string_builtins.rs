@@ -1,20 +1,93 @@
+use super::array_builtins::{clamp_index, wrong_arguments};
+use super::{Builtin, MethodRegistry};
 use crate::object::Object;
 
+// Longest string, in bytes, that `repeat` and `padStart` will build
+const MAX_LENGTH: usize = 1 << 30;
+
+const PROPERTIES: [&str; 15] = [
+    "length",
+    "chars",
+    "bytes",
+    "codepoints",
+    "is_empty",
+    "is_numeric",
+    "is_alpha",
+    "is_alphanumeric",
+    "is_ascii",
+    "is_capitalized",
+    "is_lowercase",
+    "is_uppercase",
+    "is_titlecase",
+    "is_whitespace",
+    "is_punctuation",
+];
+
+const METHODS: [&str; 15] = [
+    "split",
+    "replace",
+    "trim",
+    "trimStart",
+    "trimEnd",
+    "toUpper",
+    "toLower",
+    "contains",
+    "startsWith",
+    "endsWith",
+    "indexOf",
+    "repeat",
+    "padStart",
+    "lines",
+    "substring",
+];
+
 pub enum DotBuiltinKind {
     Property(String),
+    Method(String, Vec<Object>),
+}
+
+pub fn define_methods(registry: &mut MethodRegistry) {
+    for name in PROPERTIES {
+        registry.define_property(
+            "String",
+            name,
+            string_member(name, |name, _| DotBuiltinKind::Property(name)),
+        );
+    }
+
+    for name in METHODS {
+        registry.define_method("String", name, string_member(name, DotBuiltinKind::Method));
+    }
+}
+
+fn string_member(name: &'static str, kind: fn(String, Vec<Object>) -> DotBuiltinKind) -> Builtin {
+    Builtin::new(name, move |_, mut args| match args.remove(0) {
+        Object::String(string) => {
+            dot_str_builtins(&string, kind(name.to_string(), args)).unwrap_or(Object::Null)
+        }
+        other => Object::Error(format!(
+            "`{}` needs a String, got {}",
+            name,
+            other.type_name()
+        )),
+    })
 }
 
 pub fn dot_str_builtins(string: &str, kind: DotBuiltinKind) -> Option<Object> {
     match kind {
         DotBuiltinKind::Property(name) => match name.as_str() {
-            "length" => Some(Object::Integer(string.len() as i64)),
-            "chars" => Some(Object::Array(
+            // Lengths and positions count chars (Unicode scalar values), not bytes
+            "length" => Some(Object::Integer(string.chars().count() as i64)),
+            "chars" => Some(Object::array(
                 string
                     .chars()
                     .map(|c| Object::String(c.to_string()))
                     .collect(),
             )),
-            "bytes" => Some(Object::Array(
+            "bytes" => Some(Object::array(
+                string.bytes().map(|b| Object::Integer(b as i64)).collect(),
+            )),
+            "codepoints" => Some(Object::array(
                 string.chars().map(|c| Object::Integer(c as i64)).collect(),
             )),
             "is_empty" => Some(Object::Boolean(string.is_empty())),
@@ -33,7 +106,7 @@ pub fn dot_str_builtins(string: &str, kind: DotBuiltinKind) -> Option<Object> {
             )),
             "is_ascii" => Some(Object::Boolean(string.is_ascii())),
             "is_capitalized" => Some(Object::Boolean(
-                string.chars().next().unwrap().is_uppercase(),
+                string.chars().next().is_some_and(char::is_uppercase),
             )),
             "is_lowercase" => Some(Object::Boolean(
                 string
@@ -45,11 +118,7 @@ pub fn dot_str_builtins(string: &str, kind: DotBuiltinKind) -> Option<Object> {
                     .chars()
                     .all(|c| c.is_uppercase() || c.is_whitespace()),
             )),
-            "is_titlecase" => Some(Object::Boolean(
-                string
-                    .chars()
-                    .all(|c| c.is_uppercase() || c.is_whitespace()),
-            )),
+            "is_titlecase" => Some(Object::Boolean(is_titlecase(string))),
             "is_whitespace" => Some(Object::Boolean(
                 string
                     .chars()
@@ -62,5 +131,168 @@ pub fn dot_str_builtins(string: &str, kind: DotBuiltinKind) -> Option<Object> {
             )),
             _ => Some(Object::Error(format!("No property named {}", name))),
         },
+        DotBuiltinKind::Method(name, args) => Some(str_method(string, &name, &args)),
+    }
+}
+
+/// Every word starts with an upper case letter and continues in lower case,
+/// anything that is not a letter separates words: "Hello World", "Tammr-Script".
+fn is_titlecase(string: &str) -> bool {
+    let mut in_word = false;
+    let mut has_letters = false;
+
+    for c in string.chars() {
+        if c.is_uppercase() {
+            if in_word {
+                return false;
+            }
+            in_word = true;
+            has_letters = true;
+        } else if c.is_lowercase() {
+            if !in_word {
+                return false;
+            }
+        } else {
+            in_word = false;
+        }
+    }
+
+    has_letters
+}
+
+fn string_argument<'a>(method: &str, arg: &'a Object) -> Result<&'a str, Object> {
+    match arg {
+        Object::String(string) => Ok(string),
+        _ => Err(Object::Error(format!(
+            "Argument to `{}` must be STRING, got {}",
+            method, arg
+        ))),
     }
 }
+
+fn integer_argument(method: &str, arg: &Object) -> Result<i64, Object> {
+    match arg {
+        Object::Integer(int) => Ok(*int),
+        _ => Err(Object::Error(format!(
+            "Argument to `{}` must be INTEGER, got {}",
+            method, arg
+        ))),
+    }
+}
+
+fn strings(parts: impl Iterator<Item = impl Into<String>>) -> Object {
+    Object::array(parts.map(|part| Object::String(part.into())).collect())
+}
+
+fn str_method(string: &str, name: &str, args: &[Object]) -> Object {
+    let arity = match name {
+        "trim" | "trimStart" | "trimEnd" | "toUpper" | "toLower" | "lines" => 0..=0,
+        "split" => 0..=1,
+        "contains" | "startsWith" | "endsWith" | "indexOf" | "repeat" => 1..=1,
+        "padStart" | "substring" => 1..=2,
+        "replace" => 2..=2,
+        _ => 0..=usize::MAX,
+    };
+
+    if !arity.contains(&args.len()) {
+        let expected = if arity.start() == arity.end() {
+            arity.start().to_string()
+        } else {
+            format!("{} or {}", arity.start(), arity.end())
+        };
+        return wrong_arguments(args.len(), &expected);
+    }
+
+    match str_method_result(string, name, args) {
+        Ok(result) => result,
+        Err(err) => err,
+    }
+}
+
+fn str_method_result(string: &str, name: &str, args: &[Object]) -> Result<Object, Object> {
+    let result = match name {
+        "split" => match args.first() {
+            None => strings(string.split_whitespace()),
+            Some(separator) => match string_argument(name, separator)? {
+                "" => strings(string.chars().map(String::from)),
+                separator => strings(string.split(separator)),
+            },
+        },
+        "replace" => Object::String(string.replace(
+            string_argument(name, &args[0])?,
+            string_argument(name, &args[1])?,
+        )),
+        "trim" => Object::String(string.trim().to_string()),
+        "trimStart" => Object::String(string.trim_start().to_string()),
+        "trimEnd" => Object::String(string.trim_end().to_string()),
+        "toUpper" => Object::String(string.to_uppercase()),
+        "toLower" => Object::String(string.to_lowercase()),
+        "contains" => Object::Boolean(string.contains(string_argument(name, &args[0])?)),
+        "startsWith" => Object::Boolean(string.starts_with(string_argument(name, &args[0])?)),
+        "endsWith" => Object::Boolean(string.ends_with(string_argument(name, &args[0])?)),
+        "indexOf" => {
+            let needle = string_argument(name, &args[0])?;
+
+            // Counted in characters, like indexing and `substring`
+            Object::Integer(
+                string
+                    .find(needle)
+                    .map_or(-1, |i| string[..i].chars().count() as i64),
+            )
+        }
+        "repeat" => match integer_argument(name, &args[0])? {
+            count if count < 0 => {
+                return Err(Object::Error(format!(
+                    "Count for `repeat` must not be negative, got {}",
+                    count
+                )))
+            }
+            count => match string.len().checked_mul(count as usize) {
+                Some(length) if length <= MAX_LENGTH => {
+                    Object::String(string.repeat(count as usize))
+                }
+                _ => return Err(too_long(name)),
+            },
+        },
+        "padStart" => {
+            let width = integer_argument(name, &args[0])?.max(0) as usize;
+            let fill = match args.get(1) {
+                Some(fill) => string_argument(name, fill)?,
+                None => " ",
+            };
+
+            if width > MAX_LENGTH {
+                return Err(too_long(name));
+            }
+
+            let missing = width.saturating_sub(string.chars().count());
+            if fill.is_empty() || missing == 0 {
+                Object::String(string.to_string())
+            } else {
+                let padding: String = fill.chars().cycle().take(missing).collect();
+                Object::String(padding + string)
+            }
+        }
+        "lines" => strings(string.lines()),
+        "substring" => {
+            let chars = string.chars().collect::<Vec<_>>();
+            let start = clamp_index(integer_argument(name, &args[0])?, chars.len());
+            let end = match args.get(1) {
+                Some(end) => clamp_index(integer_argument(name, end)?, chars.len()),
+                None => chars.len(),
+            };
+
+            Object::String(chars[start..end.max(start)].iter().collect())
+        }
+        _ => return Err(Object::Error(format!("No method named {} on String", name))),
+    };
+
+    Ok(result)
+}
+
+fn too_long(name: &str) -> Object {
+    Object::Error(format!(
+        "Result of `{}` would be longer than {} bytes",
+        name, MAX_LENGTH
+    ))
+}
@@ -0,0 +1,831 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::iter::Peekable;
+use std::rc::Rc;
+use std::str::Chars;
+
+use super::{Context, NativeModule};
+use crate::object::{HashKey, Object, OrderedHash, Visited};
+
+type Table = Rc<RefCell<OrderedHash>>;
+
+/// How many arrays and inline tables may be open at once, as in JSON
+const MAX_DEPTH: usize = 128;
+
+struct TomlParser<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    col: usize,
+    root: Table,
+    current: Vec<String>,
+    defined_tables: HashSet<Vec<String>>,
+    depth: usize,
+}
+
+fn key_object(key: &str) -> HashKey {
+    HashKey::from(key)
+}
+
+fn find(table: &Table, key: &str) -> Option<Object> {
+    table.borrow().get(&key_object(key)).cloned()
+}
+
+fn new_table() -> Table {
+    Rc::new(RefCell::new(OrderedHash::new()))
+}
+
+impl<'a> TomlParser<'a> {
+    fn new(src: &'a str) -> Self {
+        Self {
+            chars: src.chars().peekable(),
+            line: 1,
+            col: 1,
+            root: new_table(),
+            current: Vec::new(),
+            defined_tables: HashSet::new(),
+            depth: 0,
+        }
+    }
+
+    fn error(&self, msg: &str) -> String {
+        format!(
+            "TOML parse error at line {}, column {}: {}",
+            self.line, self.col, msg
+        )
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+
+        if c == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+
+        Some(c)
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.advance();
+                Ok(())
+            }
+            Some(c) => Err(self.error(&format!("expected '{}', found '{}'", expected, c))),
+            None => Err(self.error(&format!("expected '{}', found end of input", expected))),
+        }
+    }
+
+    fn skip_spaces(&mut self) {
+        while let Some(' ' | '\t') = self.peek() {
+            self.advance();
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            while !matches!(self.peek(), Some('\n') | None) {
+                self.advance();
+            }
+        }
+    }
+
+    /// Skips whitespace, newlines and comments, used inside arrays.
+    fn skip_blank(&mut self) {
+        loop {
+            self.skip_spaces();
+            self.skip_comment();
+            match self.peek() {
+                Some('\n' | '\r') => {
+                    self.advance();
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn end_of_line(&mut self) -> Result<(), String> {
+        self.skip_spaces();
+        self.skip_comment();
+        self.eat('\r');
+
+        match self.peek() {
+            None => Ok(()),
+            Some('\n') => {
+                self.advance();
+                Ok(())
+            }
+            Some(c) => Err(self.error(&format!("expected end of line, found '{}'", c))),
+        }
+    }
+
+    fn parse_document(mut self) -> Result<Object, String> {
+        loop {
+            self.skip_blank();
+
+            match self.peek() {
+                None => break,
+                Some('[') => self.parse_table_header()?,
+                Some(_) => {
+                    let (path, value) = self.parse_key_value()?;
+                    let mut full = self.current.clone();
+                    full.extend(path);
+                    self.insert(&full, value)?;
+                }
+            }
+
+            self.end_of_line()?;
+        }
+
+        Ok(Object::Hash(self.root))
+    }
+
+    fn parse_table_header(&mut self) -> Result<(), String> {
+        self.expect('[')?;
+        let is_array = self.eat('[');
+
+        self.skip_spaces();
+        let path = self.parse_key()?;
+        self.skip_spaces();
+
+        self.expect(']')?;
+        if is_array {
+            self.expect(']')?;
+        }
+
+        let (parent, last) = path.split_at(path.len() - 1);
+        let line = self.line;
+
+        if is_array {
+            // Each new element starts over, its sub tables may be defined again
+            self.defined_tables
+                .retain(|defined| !defined.starts_with(&path));
+
+            let table = self.navigate(parent)?;
+            match find(&table, &last[0]) {
+                Some(Object::Array(tables)) => tables.borrow_mut().push(Object::Hash(new_table())),
+                Some(_) => {
+                    return Err(format!(
+                        "TOML parse error at line {}: `{}` is not an array of tables",
+                        line,
+                        path.join(".")
+                    ))
+                }
+                None => {
+                    table.borrow_mut().insert(
+                        key_object(&last[0]),
+                        Object::array(vec![Object::Hash(new_table())]),
+                    );
+                }
+            }
+        } else {
+            if !self.defined_tables.insert(path.clone()) {
+                return Err(format!(
+                    "TOML parse error at line {}: table `{}` is defined twice",
+                    line,
+                    path.join(".")
+                ));
+            }
+
+            let table = self.navigate(parent)?;
+            match find(&table, &last[0]) {
+                Some(Object::Hash(_)) => {}
+                Some(_) => {
+                    return Err(format!(
+                        "TOML parse error at line {}: `{}` is already a value",
+                        line,
+                        path.join(".")
+                    ))
+                }
+                None => {
+                    table
+                        .borrow_mut()
+                        .insert(key_object(&last[0]), Object::Hash(new_table()));
+                }
+            }
+        }
+
+        self.current = path;
+        Ok(())
+    }
+
+    /// Walks from the root to the table at `path`, creating tables on the way.
+    /// Arrays of tables resolve to their last element, as the spec requires.
+    fn navigate(&mut self, path: &[String]) -> Result<Table, String> {
+        let line = self.line;
+        let mut table = self.root.clone();
+
+        for (i, key) in path.iter().enumerate() {
+            let next = match find(&table, key) {
+                None => {
+                    let next = new_table();
+                    table
+                        .borrow_mut()
+                        .insert(key_object(key), Object::Hash(next.clone()));
+                    next
+                }
+                Some(Object::Hash(next)) => next,
+                Some(Object::Array(items)) => match items.borrow().last() {
+                    Some(Object::Hash(next)) => next.clone(),
+                    _ => return Err(Self::not_a_table(line, &path[..=i])),
+                },
+                _ => return Err(Self::not_a_table(line, &path[..=i])),
+            };
+
+            table = next;
+        }
+
+        Ok(table)
+    }
+
+    fn not_a_table(line: usize, path: &[String]) -> String {
+        format!(
+            "TOML parse error at line {}: `{}` is not a table",
+            line,
+            path.join(".")
+        )
+    }
+
+    fn insert(&mut self, path: &[String], value: Object) -> Result<(), String> {
+        let line = self.line;
+        let (parent, last) = path.split_at(path.len() - 1);
+        let table = self.navigate(parent)?;
+
+        if find(&table, &last[0]).is_some() {
+            return Err(format!(
+                "TOML parse error at line {}: duplicate key `{}`",
+                line,
+                path.join(".")
+            ));
+        }
+
+        table.borrow_mut().insert(key_object(&last[0]), value);
+        Ok(())
+    }
+
+    fn parse_key(&mut self) -> Result<Vec<String>, String> {
+        let mut path = Vec::new();
+
+        loop {
+            self.skip_spaces();
+
+            let part = match self.peek() {
+                Some('"') => self.parse_basic_string()?,
+                Some('\'') => self.parse_literal_string()?,
+                Some(c) if c.is_ascii_alphanumeric() || c == '_' || c == '-' => {
+                    let mut key = String::new();
+                    while let Some(c) = self.peek() {
+                        if !(c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                            break;
+                        }
+                        key.push(c);
+                        self.advance();
+                    }
+                    key
+                }
+                _ => return Err(self.error("expected a key")),
+            };
+
+            path.push(part);
+            self.skip_spaces();
+
+            if !self.eat('.') {
+                return Ok(path);
+            }
+        }
+    }
+
+    fn parse_key_value(&mut self) -> Result<(Vec<String>, Object), String> {
+        let path = self.parse_key()?;
+
+        self.skip_spaces();
+        self.expect('=')?;
+        self.skip_spaces();
+
+        let value = self.parse_value()?;
+        Ok((path, value))
+    }
+
+    fn parse_value(&mut self) -> Result<Object, String> {
+        match self.peek() {
+            Some('"') => Ok(Object::String(self.parse_basic_string()?)),
+            Some('\'') => Ok(Object::String(self.parse_literal_string()?)),
+            Some('[' | '{') if self.depth == MAX_DEPTH => {
+                Err(self.error(&format!("nested deeper than {} levels", MAX_DEPTH)))
+            }
+            Some(c @ ('[' | '{')) => {
+                self.depth += 1;
+                let value = if c == '[' {
+                    self.parse_array()
+                } else {
+                    self.parse_inline_table()
+                };
+                self.depth -= 1;
+                value
+            }
+            Some(_) => self.parse_bare_value(),
+            None => Err(self.error("expected a value")),
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Object, String> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+
+        loop {
+            self.skip_blank();
+            if self.eat(']') {
+                return Ok(Object::array(items));
+            }
+
+            items.push(self.parse_value()?);
+
+            self.skip_blank();
+            if self.eat(']') {
+                return Ok(Object::array(items));
+            }
+            if !self.eat(',') {
+                return Err(self.error("expected ',' or ']' in array"));
+            }
+        }
+    }
+
+    fn parse_inline_table(&mut self) -> Result<Object, String> {
+        self.expect('{')?;
+        let table = new_table();
+
+        self.skip_spaces();
+        if self.eat('}') {
+            return Ok(Object::Hash(table));
+        }
+
+        loop {
+            let (path, value) = self.parse_key_value()?;
+
+            let mut target = table.clone();
+            for key in &path[..path.len() - 1] {
+                target = match find(&target, key) {
+                    None => {
+                        let next = new_table();
+                        target
+                            .borrow_mut()
+                            .insert(key_object(key), Object::Hash(next.clone()));
+                        next
+                    }
+                    Some(Object::Hash(next)) => next,
+                    _ => return Err(self.error(&format!("`{}` is not a table", key))),
+                };
+            }
+
+            let last = &path[path.len() - 1];
+            if find(&target, last).is_some() {
+                return Err(self.error(&format!("duplicate key `{}`", last)));
+            }
+            target.borrow_mut().insert(key_object(last), value);
+
+            self.skip_spaces();
+            if self.eat('}') {
+                return Ok(Object::Hash(table));
+            }
+            if !self.eat(',') {
+                return Err(self.error("expected ',' or '}' in inline table"));
+            }
+            self.skip_spaces();
+        }
+    }
+
+    fn parse_escape(&mut self, string: &mut String) -> Result<(), String> {
+        let escaped = match self.advance() {
+            Some('b') => '\u{8}',
+            Some('t') => '\t',
+            Some('n') => '\n',
+            Some('f') => '\u{c}',
+            Some('r') => '\r',
+            Some('e') => '\u{1b}',
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some(kind @ ('u' | 'U')) => {
+                let digits = if kind == 'u' { 4 } else { 8 };
+                let mut code = 0;
+                for _ in 0..digits {
+                    match self.advance().and_then(|c| c.to_digit(16)) {
+                        Some(digit) => code = code * 16 + digit,
+                        None => return Err(self.error("invalid unicode escape")),
+                    }
+                }
+                match char::from_u32(code) {
+                    Some(c) => c,
+                    None => return Err(self.error("invalid unicode escape")),
+                }
+            }
+            _ => return Err(self.error("invalid escape sequence")),
+        };
+
+        string.push(escaped);
+        Ok(())
+    }
+
+    fn parse_basic_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut string = String::new();
+
+        if self.eat('"') {
+            if !self.eat('"') {
+                return Ok(string);
+            }
+            return self.parse_multiline_basic_string();
+        }
+
+        loop {
+            match self.advance() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some('"') => return Ok(string),
+                Some('\\') => self.parse_escape(&mut string)?,
+                Some(c) => string.push(c),
+            }
+        }
+    }
+
+    fn parse_multiline_basic_string(&mut self) -> Result<String, String> {
+        let mut string = String::new();
+
+        // A newline right after the opening quotes is trimmed
+        self.eat('\r');
+        self.eat('\n');
+
+        loop {
+            match self.advance() {
+                None => return Err(self.error("unterminated string")),
+                Some('"') if self.peek() == Some('"') => {
+                    self.advance();
+                    if self.eat('"') {
+                        // Up to two quotes may sit right before the closing three
+                        while self.peek() == Some('"') && !string.ends_with("\"\"") {
+                            self.advance();
+                            string.push('"');
+                        }
+                        return Ok(string);
+                    }
+                    string.push_str("\"\"");
+                }
+                Some('\\') => match self.peek() {
+                    Some(' ' | '\t' | '\r' | '\n') => {
+                        // Line ending backslash trims the newline and leading whitespace
+                        while let Some(' ' | '\t' | '\r' | '\n') = self.peek() {
+                            self.advance();
+                        }
+                    }
+                    _ => self.parse_escape(&mut string)?,
+                },
+                Some(c) => string.push(c),
+            }
+        }
+    }
+
+    fn parse_literal_string(&mut self) -> Result<String, String> {
+        self.expect('\'')?;
+        let mut string = String::new();
+
+        if self.eat('\'') {
+            if !self.eat('\'') {
+                return Ok(string);
+            }
+
+            self.eat('\r');
+            self.eat('\n');
+
+            loop {
+                match self.advance() {
+                    None => return Err(self.error("unterminated string")),
+                    Some('\'') if self.peek() == Some('\'') => {
+                        self.advance();
+                        if self.eat('\'') {
+                            while self.peek() == Some('\'') && !string.ends_with("''") {
+                                self.advance();
+                                string.push('\'');
+                            }
+                            return Ok(string);
+                        }
+                        string.push_str("''");
+                    }
+                    Some(c) => string.push(c),
+                }
+            }
+        }
+
+        loop {
+            match self.advance() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some('\'') => return Ok(string),
+                Some(c) => string.push(c),
+            }
+        }
+    }
+
+    /// Numbers, booleans and dates. Dates have no Tammr type, they stay strings.
+    fn parse_bare_value(&mut self) -> Result<Object, String> {
+        let mut raw = String::new();
+
+        while let Some(c) = self.peek() {
+            let in_datetime = c == ' '
+                && raw.len() == 10
+                && raw.as_bytes()[4] == b'-'
+                && raw.as_bytes()[7] == b'-';
+
+            if !(c.is_ascii_alphanumeric() || "+-_.:".contains(c) || in_datetime) {
+                break;
+            }
+            raw.push(c);
+            self.advance();
+        }
+
+        let raw = raw.trim_end();
+
+        match raw {
+            "" => Err(self.error("expected a value")),
+            "true" => Ok(Object::Boolean(true)),
+            "false" => Ok(Object::Boolean(false)),
+            "inf" | "+inf" => Ok(Object::Float(f64::INFINITY)),
+            "-inf" => Ok(Object::Float(f64::NEG_INFINITY)),
+            "nan" | "+nan" | "-nan" => Ok(Object::Float(f64::NAN)),
+            _ if raw.len() >= 8 && raw.as_bytes()[4] == b'-' || raw.contains(':') => {
+                Ok(Object::String(raw.to_string()))
+            }
+            _ => self.parse_number(raw),
+        }
+    }
+
+    fn parse_number(&self, raw: &str) -> Result<Object, String> {
+        let invalid = || self.error(&format!("invalid value `{}`", raw));
+
+        if raw.starts_with('_') || raw.ends_with('_') || raw.contains("__") {
+            return Err(invalid());
+        }
+
+        let digits = raw.replace('_', "");
+
+        for (prefix, radix) in [("0x", 16), ("0o", 8), ("0b", 2)] {
+            if let Some(rest) = digits.strip_prefix(prefix) {
+                return i64::from_str_radix(rest, radix)
+                    .map(Object::Integer)
+                    .map_err(|_| invalid());
+            }
+        }
+
+        let unsigned = digits.trim_start_matches(['+', '-']);
+        if unsigned.len() > 1
+            && unsigned.starts_with('0')
+            && !unsigned.starts_with("0.")
+            && !unsigned[1..].starts_with(['e', 'E'])
+        {
+            return Err(invalid());
+        }
+
+        if digits.contains(['.', 'e', 'E']) {
+            if digits.contains(".e") || digits.contains(".E") || digits.ends_with('.') {
+                return Err(invalid());
+            }
+            return digits
+                .parse::<f64>()
+                .map(Object::Float)
+                .map_err(|_| invalid());
+        }
+
+        digits
+            .parse::<i64>()
+            .map(Object::Integer)
+            .map_err(|_| invalid())
+    }
+}
+
+pub fn parse_toml(src: &str) -> Result<Object, String> {
+    TomlParser::new(src).parse_document()
+}
+
+fn write_key(out: &mut String, key: &HashKey) {
+    let key = key.to_string();
+
+    let is_bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+
+    if is_bare {
+        out.push_str(&key);
+    } else {
+        write_string(out, &key);
+    }
+}
+
+fn write_string(out: &mut String, string: &str) {
+    out.push('"');
+
+    for c in string.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 || c == '\u{7f}' => {
+                out.push_str(&format!("\\u{:04X}", c as u32))
+            }
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+}
+
+fn contains_itself() -> String {
+    "Cannot serialize a value that contains itself to TOML".to_string()
+}
+
+fn write_inline(out: &mut String, value: &Object, visited: &mut Visited) -> Result<(), String> {
+    match value {
+        Object::String(string) => write_string(out, string),
+        Object::Integer(int) => out.push_str(&int.to_string()),
+        Object::Float(float) if float.is_nan() => out.push_str("nan"),
+        Object::Float(float) if float.is_infinite() => {
+            out.push_str(if *float > 0.0 { "inf" } else { "-inf" })
+        }
+        Object::Float(float) => out.push_str(&format!("{:?}", float)),
+        Object::Boolean(bool) => out.push_str(&bool.to_string()),
+        Object::Array(items) => {
+            if !visited.enter(items) {
+                return Err(contains_itself());
+            }
+
+            out.push('[');
+            for (i, item) in items.borrow().iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_inline(out, item, visited)?;
+            }
+            out.push(']');
+            visited.leave(items);
+        }
+        Object::Hash(hash) => {
+            if !visited.enter(hash) {
+                return Err(contains_itself());
+            }
+
+            let pairs = hash.borrow();
+            out.push('{');
+            for (i, (key, value)) in pairs.iter().enumerate() {
+                out.push_str(if i > 0 { ", " } else { " " });
+                write_key(out, key);
+                out.push_str(" = ");
+                write_inline(out, value, visited)?;
+            }
+            out.push_str(if pairs.is_empty() { "}" } else { " }" });
+            visited.leave(hash);
+        }
+        Object::Null => return Err("TOML has no null, remove the key instead".to_string()),
+        _ => {
+            return Err(format!(
+                "Cannot serialize {} to TOML",
+                value.type_name().to_lowercase()
+            ))
+        }
+    }
+
+    Ok(())
+}
+
+fn is_table_array(value: &Object) -> bool {
+    match value {
+        Object::Array(items) => {
+            let items = items.borrow();
+            !items.is_empty() && items.iter().all(|item| matches!(item, Object::Hash(_)))
+        }
+        _ => false,
+    }
+}
+
+fn write_table(
+    out: &mut String,
+    path: &[String],
+    table: &Table,
+    visited: &mut Visited,
+) -> Result<(), String> {
+    if !visited.enter(table) {
+        return Err(contains_itself());
+    }
+
+    let pairs = table.borrow();
+
+    // Plain values have to come before any sub table header
+    for (key, value) in pairs.iter() {
+        if matches!(value, Object::Hash(_)) || is_table_array(value) {
+            continue;
+        }
+
+        write_key(out, key);
+        out.push_str(" = ");
+        write_inline(out, value, visited)?;
+        out.push('\n');
+    }
+
+    for (key, value) in pairs.iter() {
+        let mut header = String::new();
+        for part in path {
+            write_key(&mut header, &HashKey::from(part.as_str()));
+            header.push('.');
+        }
+        write_key(&mut header, key);
+
+        let mut child_path = path.to_vec();
+        child_path.push(key.to_string());
+
+        match value {
+            Object::Hash(child) => {
+                if !out.is_empty() {
+                    out.push('\n');
+                }
+                out.push_str(&format!("[{}]\n", header));
+                write_table(out, &child_path, child, visited)?;
+            }
+            Object::Array(items) if is_table_array(value) => {
+                for item in items.borrow().iter() {
+                    if let Object::Hash(child) = item {
+                        if !out.is_empty() {
+                            out.push('\n');
+                        }
+                        out.push_str(&format!("[[{}]]\n", header));
+                        write_table(out, &child_path, child, visited)?;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    visited.leave(table);
+    Ok(())
+}
+
+pub fn stringify_toml(obj: &Object) -> Result<String, String> {
+    match obj {
+        Object::Hash(pairs) => {
+            let mut out = String::new();
+            write_table(&mut out, &[], pairs, &mut Visited::default())?;
+            Ok(out)
+        }
+        _ => Err(format!("A TOML document must be a HASH, got {}", obj)),
+    }
+}
+
+pub fn toml_parse(_: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() != 1 {
+        return Object::Error(format!(
+            "Wrong number of arguments. Got {}, expected 1",
+            args.len()
+        ));
+    }
+
+    match &args[0] {
+        Object::String(src) => parse_toml(src).unwrap_or_else(Object::Error),
+        _ => Object::Error(format!(
+            "Argument to `toml.parse` must be STRING, got {}",
+            args[0]
+        )),
+    }
+}
+
+pub fn toml_stringify(_: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() != 1 {
+        return Object::Error(format!(
+            "Wrong number of arguments. Got {}, expected 1",
+            args.len()
+        ));
+    }
+
+    match stringify_toml(&args[0]) {
+        Ok(toml) => Object::String(toml),
+        Err(msg) => Object::Error(msg),
+    }
+}
+
+/// The `toml` namespace, so scripts can call `toml.parse(...)`.
+pub fn toml_module() -> NativeModule {
+    NativeModule::new("toml")
+        .function("parse", toml_parse)
+        .function("stringify", toml_stringify)
+}
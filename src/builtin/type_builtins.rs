@@ -0,0 +1,95 @@
+use super::array_builtins::wrong_arguments;
+use super::Context;
+use crate::object::Object;
+
+fn single_argument(args: &[Object]) -> Result<&Object, Object> {
+    match args {
+        [arg] => Ok(arg),
+        _ => Err(wrong_arguments(args.len(), "1")),
+    }
+}
+
+fn cannot_convert(value: &Object, to: &str) -> Object {
+    match value {
+        Object::String(string) => Object::Error(format!("Cannot convert {:?} to {}", string, to)),
+        other => Object::Error(format!("Cannot convert {} to {}", other.hint_name(), to)),
+    }
+}
+
+/// `type(x)`, the type name as spelled in hints and `is` tests
+pub fn type_of(_: &mut Context, args: Vec<Object>) -> Object {
+    match single_argument(&args) {
+        Ok(arg) => Object::String(arg.hint_name().to_string()),
+        Err(err) => err,
+    }
+}
+
+/// `int(x)`, floats are truncated towards zero
+pub fn to_int(_: &mut Context, args: Vec<Object>) -> Object {
+    let arg = match single_argument(&args) {
+        Ok(arg) => arg,
+        Err(err) => return err,
+    };
+
+    match arg {
+        Object::Integer(int) => Object::Integer(*int),
+        Object::Float(float) if float.is_finite() && float.abs() < i64::MAX as f64 => {
+            Object::Integer(float.trunc() as i64)
+        }
+        Object::Boolean(bool) => Object::Integer(*bool as i64),
+        Object::String(string) => match string.trim().parse::<i64>() {
+            Ok(int) => Object::Integer(int),
+            Err(_) => cannot_convert(arg, "int"),
+        },
+        _ => cannot_convert(arg, "int"),
+    }
+}
+
+/// `float(x)`
+pub fn to_float(_: &mut Context, args: Vec<Object>) -> Object {
+    let arg = match single_argument(&args) {
+        Ok(arg) => arg,
+        Err(err) => return err,
+    };
+
+    match arg {
+        Object::Integer(int) => Object::Float(*int as f64),
+        Object::Float(float) => Object::Float(*float),
+        Object::Boolean(bool) => Object::Float(*bool as i64 as f64),
+        // Rust also accepts "inf" and "NaN", which are not numbers in Tammr
+        Object::String(string) => match string.trim().parse::<f64>() {
+            Ok(float) if float.is_finite() => Object::Float(float),
+            _ => cannot_convert(arg, "float"),
+        },
+        _ => cannot_convert(arg, "float"),
+    }
+}
+
+/// `str(x)`, how `println` would show the value
+pub fn to_str(_: &mut Context, args: Vec<Object>) -> Object {
+    match single_argument(&args) {
+        Ok(arg) => Object::String(arg.to_string()),
+        Err(err) => err,
+    }
+}
+
+/// `bool(x)`, accepts booleans, "true" and "false", and numbers where only
+/// zero is false
+pub fn to_bool(_: &mut Context, args: Vec<Object>) -> Object {
+    let arg = match single_argument(&args) {
+        Ok(arg) => arg,
+        Err(err) => return err,
+    };
+
+    match arg {
+        Object::Boolean(bool) => Object::Boolean(*bool),
+        Object::Integer(int) => Object::Boolean(*int != 0),
+        Object::Float(float) => Object::Boolean(*float != 0.0),
+        Object::String(string) => match string.trim() {
+            "true" => Object::Boolean(true),
+            "false" => Object::Boolean(false),
+            _ => cannot_convert(arg, "bool"),
+        },
+        _ => cannot_convert(arg, "bool"),
+    }
+}
@@ -0,0 +1,639 @@
+use super::{Context, NativeModule};
+use crate::object::{HashKey, Object, OrderedHash};
+
+/// A YAML reader for the subset config files use: block mappings and
+/// sequences, flow collections on a single line, plain and quoted scalars,
+/// `|` and `>` block scalars and comments. Anchors, tags and multiple
+/// documents are rejected rather than silently misread.
+struct YamlParser<'a> {
+    lines: Vec<Line<'a>>,
+    index: usize,
+    depth: usize,
+}
+
+/// How many collections may be open at once, block and flow together, as in
+/// JSON
+const MAX_DEPTH: usize = 128;
+
+#[derive(Clone)]
+struct Line<'a> {
+    number: usize,
+    indent: usize,
+    text: &'a str,
+}
+
+fn error(line: usize, msg: &str) -> String {
+    format!("YAML parse error at line {}: {}", line, msg)
+}
+
+fn too_deep(line: usize) -> String {
+    error(line, &format!("nested deeper than {} levels", MAX_DEPTH))
+}
+
+/// Cuts a trailing `# comment`, leaving `#` inside quotes and words alone.
+fn strip_comment(text: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '#') if previous == ' ' || previous == '\t' => return text[..i].trim_end(),
+            _ => {}
+        }
+        previous = c;
+    }
+
+    text.trim_end()
+}
+
+/// Finds the `:` that separates a mapping key from its value.
+fn split_key(text: &str) -> Option<(&str, &str)> {
+    let mut quote = None;
+    let mut depth = 0;
+
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') if i == 0 => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '[' | '{') => depth += 1,
+            (None, ']' | '}') => depth -= 1,
+            (None, ':') if depth == 0 => {
+                let rest = &text[i + 1..];
+                if rest.is_empty() || rest.starts_with([' ', '\t']) {
+                    return Some((text[..i].trim_end(), rest.trim_start()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn is_sequence_item(text: &str) -> bool {
+    text == "-" || text.starts_with("- ")
+}
+
+impl<'a> YamlParser<'a> {
+    fn new(src: &'a str) -> Self {
+        let lines = src
+            .lines()
+            .enumerate()
+            .map(|(i, raw)| {
+                let text = raw.trim_start_matches(' ');
+                Line {
+                    number: i + 1,
+                    indent: raw.len() - text.len(),
+                    text,
+                }
+            })
+            .collect();
+
+        Self {
+            lines,
+            index: 0,
+            depth: 0,
+        }
+    }
+
+    fn skip_blank(&mut self) {
+        while let Some(line) = self.lines.get(self.index) {
+            if !strip_comment(line.text).is_empty() {
+                return;
+            }
+            self.index += 1;
+        }
+    }
+
+    /// The next line with content. Block scalars read their lines directly,
+    /// since a tab after their indentation is part of the text.
+    fn current(&mut self) -> Result<Option<Line<'a>>, String> {
+        self.skip_blank();
+
+        match self.lines.get(self.index) {
+            Some(line) if line.text.starts_with('\t') => {
+                Err(error(line.number, "tabs are not allowed in indentation"))
+            }
+            line => Ok(line.cloned()),
+        }
+    }
+
+    fn parse_document(mut self) -> Result<Object, String> {
+        if let Some(line) = self.current()? {
+            if line.indent == 0 && strip_comment(line.text) == "---" {
+                self.index += 1;
+            }
+        }
+
+        let value = match self.current()? {
+            Some(line) => self.parse_block(line.indent)?,
+            None => return Ok(Object::Null),
+        };
+
+        if let Some(line) = self.current()? {
+            let text = strip_comment(line.text);
+            if text != "..." {
+                return Err(error(
+                    line.number,
+                    if text == "---" {
+                        "multiple documents are not supported"
+                    } else {
+                        "unexpected indentation"
+                    },
+                ));
+            }
+        }
+
+        Ok(value)
+    }
+
+    fn parse_block(&mut self, indent: usize) -> Result<Object, String> {
+        let line = match self.current()? {
+            Some(line) => line,
+            None => return Ok(Object::Null),
+        };
+
+        let text = strip_comment(line.text);
+
+        if is_sequence_item(text) || split_key(text).is_some() {
+            if self.depth == MAX_DEPTH {
+                return Err(too_deep(line.number));
+            }
+
+            self.depth += 1;
+            let value = if is_sequence_item(text) {
+                self.parse_sequence(indent)
+            } else {
+                self.parse_mapping(indent)
+            };
+            self.depth -= 1;
+            value
+        } else {
+            // A lone scalar, possibly the whole document
+            self.index += 1;
+            self.parse_inline(line.number, text)
+        }
+    }
+
+    fn parse_sequence(&mut self, indent: usize) -> Result<Object, String> {
+        let mut items = Vec::new();
+
+        while let Some(line) = self.current()? {
+            let text = strip_comment(line.text);
+            if line.indent != indent || !is_sequence_item(text) {
+                break;
+            }
+
+            let rest = text[1..].trim_start();
+
+            if rest.is_empty() {
+                self.index += 1;
+                items.push(self.parse_nested(indent)?);
+                continue;
+            }
+
+            if is_sequence_item(rest) || split_key(rest).is_some() {
+                // `- key: value` opens a mapping whose keys line up with `key`
+                let offset = line.text.len() - line.text[1..].trim_start().len();
+                self.lines[self.index] = Line {
+                    number: line.number,
+                    indent: indent + offset,
+                    text: &line.text[offset..],
+                };
+                items.push(self.parse_block(indent + offset)?);
+                continue;
+            }
+
+            self.index += 1;
+            items.push(self.parse_value(line.number, indent, rest)?);
+        }
+
+        Ok(Object::array(items))
+    }
+
+    fn parse_mapping(&mut self, indent: usize) -> Result<Object, String> {
+        let mut pairs = OrderedHash::new();
+
+        while let Some(line) = self.current()? {
+            if line.indent < indent {
+                break;
+            }
+            if line.indent > indent {
+                return Err(error(line.number, "unexpected indentation"));
+            }
+
+            let text = strip_comment(line.text);
+            if is_sequence_item(text) || text == "---" || text == "..." {
+                break;
+            }
+
+            let (key, rest) = match split_key(text) {
+                Some(split) => split,
+                None => return Err(error(line.number, "expected `key: value`")),
+            };
+
+            // Integer and boolean keys keep their type, anything else is a string
+            let key = match self.parse_inline(line.number, key)? {
+                Object::Null => HashKey::from(key),
+                parsed => HashKey::from_object(&parsed)
+                    .unwrap_or_else(|| HashKey::String(parsed.to_string())),
+            };
+
+            self.index += 1;
+
+            let value = if rest.is_empty() {
+                match self.current()? {
+                    // A sequence may sit at the same indent as its key
+                    Some(next)
+                        if next.indent == indent && is_sequence_item(strip_comment(next.text)) =>
+                    {
+                        self.parse_sequence(indent)?
+                    }
+                    _ => self.parse_nested(indent)?,
+                }
+            } else {
+                self.parse_value(line.number, indent, rest)?
+            };
+
+            if pairs.contains_key(&key) {
+                return Err(error(line.number, &format!("duplicate key `{}`", key)));
+            }
+
+            pairs.insert(key, value);
+        }
+
+        Ok(Object::hash(pairs))
+    }
+
+    /// The block indented under a `key:` or `-` with nothing after it.
+    fn parse_nested(&mut self, indent: usize) -> Result<Object, String> {
+        match self.current()? {
+            Some(next) if next.indent > indent => self.parse_block(next.indent),
+            _ => Ok(Object::Null),
+        }
+    }
+
+    fn parse_value(&mut self, number: usize, indent: usize, text: &str) -> Result<Object, String> {
+        match text.chars().next() {
+            Some('|' | '>') => self.parse_block_scalar(number, indent, text),
+            Some('&' | '*' | '!') => {
+                Err(error(number, "anchors, aliases and tags are not supported"))
+            }
+            _ => self.parse_inline(number, text),
+        }
+    }
+
+    fn parse_block_scalar(
+        &mut self,
+        number: usize,
+        indent: usize,
+        header: &str,
+    ) -> Result<Object, String> {
+        let folded = header.starts_with('>');
+        let chomp = &header[1..];
+        if !matches!(chomp, "" | "-" | "+") {
+            return Err(error(number, "unsupported block scalar header"));
+        }
+
+        let mut lines: Vec<String> = Vec::new();
+        let mut content_indent = None;
+
+        while let Some(line) = self.lines.get(self.index) {
+            if line.text.is_empty() {
+                lines.push(String::new());
+                self.index += 1;
+                continue;
+            }
+            if line.indent <= indent {
+                break;
+            }
+
+            let base = *content_indent.get_or_insert(line.indent);
+            if line.indent < base {
+                break;
+            }
+
+            // Indentation past the first line's is part of the text
+            lines.push(format!("{}{}", " ".repeat(line.indent - base), line.text));
+            self.index += 1;
+        }
+
+        let trailing = lines.iter().rev().take_while(|l| l.is_empty()).count();
+        let body = &lines[..lines.len() - trailing];
+
+        let mut text = if folded {
+            let mut text = String::new();
+            for (i, line) in body.iter().enumerate() {
+                if i > 0 {
+                    // Blank lines become newlines, a line break after one is dropped
+                    let previous = &body[i - 1];
+                    if line.is_empty() || line.starts_with(' ') || previous.starts_with(' ') {
+                        text.push('\n');
+                    } else if !previous.is_empty() {
+                        text.push(' ');
+                    }
+                }
+                text.push_str(line);
+            }
+            text
+        } else {
+            body.join("\n")
+        };
+
+        match chomp {
+            "-" => {}
+            "+" => text.push_str(&"\n".repeat(trailing + 1)),
+            _ if !body.is_empty() => text.push('\n'),
+            _ => {}
+        }
+
+        Ok(Object::String(text))
+    }
+
+    fn parse_inline(&self, number: usize, text: &str) -> Result<Object, String> {
+        let mut flow = FlowParser {
+            chars: text.char_indices().peekable(),
+            text,
+            number,
+            depth: self.depth,
+        };
+
+        let value = flow.parse_value(false)?;
+        flow.skip_spaces();
+
+        match flow.chars.peek() {
+            None => Ok(value),
+            Some((_, c)) => Err(error(number, &format!("unexpected '{}'", c))),
+        }
+    }
+}
+
+/// Parses flow collections (`[a, b]`, `{a: 1}`) and scalars within one line.
+struct FlowParser<'a> {
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    text: &'a str,
+    number: usize,
+    depth: usize,
+}
+
+impl FlowParser<'_> {
+    fn skip_spaces(&mut self) {
+        while let Some((_, ' ' | '\t')) = self.chars.peek() {
+            self.chars.next();
+        }
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if let Some((_, c)) = self.chars.peek() {
+            if *c == expected {
+                self.chars.next();
+                return true;
+            }
+        }
+        false
+    }
+
+    fn parse_value(&mut self, in_flow: bool) -> Result<Object, String> {
+        self.skip_spaces();
+
+        match self.chars.peek().map(|(_, c)| *c) {
+            Some('[' | '{') if self.depth == MAX_DEPTH => Err(too_deep(self.number)),
+            Some(c @ ('[' | '{')) => {
+                self.depth += 1;
+                let value = if c == '[' {
+                    self.parse_flow_sequence()
+                } else {
+                    self.parse_flow_mapping()
+                };
+                self.depth -= 1;
+                value
+            }
+            Some('"') => self.parse_double_quoted().map(Object::String),
+            Some('\'') => self.parse_single_quoted().map(Object::String),
+            Some('&' | '*' | '!') => Err(error(
+                self.number,
+                "anchors, aliases and tags are not supported",
+            )),
+            _ => Ok(resolve_plain(&self.take_plain(in_flow))),
+        }
+    }
+
+    fn take_plain(&mut self, in_flow: bool) -> String {
+        let start = match self.chars.peek() {
+            Some((i, _)) => *i,
+            None => return String::new(),
+        };
+        let mut end = start;
+
+        while let Some((i, c)) = self.chars.peek().copied() {
+            if in_flow && matches!(c, ',' | ']' | '}') {
+                break;
+            }
+            if in_flow && c == ':' {
+                let rest = &self.text[i + 1..];
+                if rest.is_empty() || rest.starts_with([' ', ',', ']', '}']) {
+                    break;
+                }
+            }
+            self.chars.next();
+            end = i + c.len_utf8();
+        }
+
+        self.text[start..end].trim_end().to_string()
+    }
+
+    fn parse_flow_sequence(&mut self) -> Result<Object, String> {
+        self.chars.next();
+        let mut items = Vec::new();
+
+        loop {
+            self.skip_spaces();
+            if self.eat(']') {
+                return Ok(Object::array(items));
+            }
+
+            items.push(self.parse_value(true)?);
+
+            self.skip_spaces();
+            if self.eat(']') {
+                return Ok(Object::array(items));
+            }
+            if !self.eat(',') {
+                return Err(error(self.number, "expected ',' or ']' in flow sequence"));
+            }
+        }
+    }
+
+    fn parse_flow_mapping(&mut self) -> Result<Object, String> {
+        self.chars.next();
+        let mut pairs = OrderedHash::new();
+
+        loop {
+            self.skip_spaces();
+            if self.eat('}') {
+                return Ok(Object::hash(pairs));
+            }
+
+            let key = match self.parse_value(true)? {
+                Object::Null => return Err(error(self.number, "expected a key")),
+                parsed => HashKey::from_object(&parsed)
+                    .unwrap_or_else(|| HashKey::String(parsed.to_string())),
+            };
+
+            self.skip_spaces();
+            let value = if self.eat(':') {
+                self.parse_value(true)?
+            } else {
+                Object::Null
+            };
+
+            pairs.insert(key, value);
+
+            self.skip_spaces();
+            if self.eat('}') {
+                return Ok(Object::hash(pairs));
+            }
+            if !self.eat(',') {
+                return Err(error(self.number, "expected ',' or '}' in flow mapping"));
+            }
+        }
+    }
+
+    fn parse_double_quoted(&mut self) -> Result<String, String> {
+        self.chars.next();
+        let mut string = String::new();
+
+        loop {
+            match self.chars.next().map(|(_, c)| c) {
+                None => return Err(error(self.number, "unterminated string")),
+                Some('"') => return Ok(string),
+                Some('\\') => {
+                    let escaped = match self.chars.next().map(|(_, c)| c) {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('0') => '\0',
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some(' ') => ' ',
+                        Some(kind @ ('x' | 'u' | 'U')) => {
+                            let digits = match kind {
+                                'x' => 2,
+                                'u' => 4,
+                                _ => 8,
+                            };
+                            let mut code = 0;
+                            for _ in 0..digits {
+                                match self.chars.next().and_then(|(_, c)| c.to_digit(16)) {
+                                    Some(digit) => code = code * 16 + digit,
+                                    None => {
+                                        return Err(error(self.number, "invalid escape sequence"))
+                                    }
+                                }
+                            }
+                            match char::from_u32(code) {
+                                Some(c) => c,
+                                None => return Err(error(self.number, "invalid escape sequence")),
+                            }
+                        }
+                        _ => return Err(error(self.number, "invalid escape sequence")),
+                    };
+                    string.push(escaped);
+                }
+                Some(c) => string.push(c),
+            }
+        }
+    }
+
+    fn parse_single_quoted(&mut self) -> Result<String, String> {
+        self.chars.next();
+        let mut string = String::new();
+
+        loop {
+            match self.chars.next().map(|(_, c)| c) {
+                None => return Err(error(self.number, "unterminated string")),
+                Some('\'') => {
+                    if self.eat('\'') {
+                        string.push('\'');
+                    } else {
+                        return Ok(string);
+                    }
+                }
+                Some(c) => string.push(c),
+            }
+        }
+    }
+}
+
+/// Gives an unquoted scalar its type, following the YAML 1.2 core schema.
+fn resolve_plain(text: &str) -> Object {
+    match text {
+        "" | "~" | "null" | "Null" | "NULL" => return Object::Null,
+        "true" | "True" | "TRUE" => return Object::Boolean(true),
+        "false" | "False" | "FALSE" => return Object::Boolean(false),
+        ".inf" | ".Inf" | ".INF" | "+.inf" | "+.Inf" | "+.INF" => {
+            return Object::Float(f64::INFINITY)
+        }
+        "-.inf" | "-.Inf" | "-.INF" => return Object::Float(f64::NEG_INFINITY),
+        ".nan" | ".NaN" | ".NAN" => return Object::Float(f64::NAN),
+        _ => {}
+    }
+
+    if let Some(hex) = text.strip_prefix("0x") {
+        if let Ok(int) = i64::from_str_radix(hex, 16) {
+            return Object::Integer(int);
+        }
+    }
+
+    if let Some(octal) = text.strip_prefix("0o") {
+        if let Ok(int) = i64::from_str_radix(octal, 8) {
+            return Object::Integer(int);
+        }
+    }
+
+    let unsigned = text.trim_start_matches(['-', '+']);
+    let looks_numeric = unsigned.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+        && unsigned
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '-' | '+'));
+
+    if looks_numeric {
+        if let Ok(int) = text.parse::<i64>() {
+            return Object::Integer(int);
+        }
+        if let Ok(float) = text.parse::<f64>() {
+            return Object::Float(float);
+        }
+    }
+
+    Object::String(text.to_string())
+}
+
+pub fn parse_yaml(src: &str) -> Result<Object, String> {
+    YamlParser::new(src).parse_document()
+}
+
+pub fn yaml_parse(_: &mut Context, args: Vec<Object>) -> Object {
+    if args.len() != 1 {
+        return Object::Error(format!(
+            "Wrong number of arguments. Got {}, expected 1",
+            args.len()
+        ));
+    }
+
+    match &args[0] {
+        Object::String(src) => parse_yaml(src).unwrap_or_else(Object::Error),
+        _ => Object::Error(format!(
+            "Argument to `yaml.parse` must be STRING, got {}",
+            args[0]
+        )),
+    }
+}
+
+/// The `yaml` namespace, so scripts can call `yaml.parse(...)`.
+pub fn yaml_module() -> NativeModule {
+    NativeModule::new("yaml").function("parse", yaml_parse)
+}
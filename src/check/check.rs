@@ -0,0 +1,749 @@
+use std::collections::{HashMap, HashSet};
+
+use super::Type;
+use crate::ast::{Expression, Identifier, Literal, Parameter, Program, Statement};
+use crate::builtin::{self, MethodRegistry};
+use crate::lexer::{Position, PrimitiveKind};
+
+/// A mistake found before running, at the line of the offending token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub position: Position,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.position.line + 1, self.message)
+    }
+}
+
+struct Binding {
+    ty: Type,
+    hint: Option<PrimitiveKind>,
+    constant: bool,
+    /// False until its `let` is reached, the name is known from the start of
+    /// the scope so functions can refer to variables declared after them
+    declared: bool,
+}
+
+impl Binding {
+    fn new(ty: Type, hint: Option<PrimitiveKind>) -> Self {
+        Self {
+            ty,
+            hint,
+            constant: false,
+            declared: true,
+        }
+    }
+}
+
+/// Infers what it can about a program without running it and reports the
+/// mistakes that would certainly fail at runtime. Anything it cannot be sure
+/// about is `Type::Unknown` and passes.
+pub struct Checker {
+    /// One scope per function body and block, the first holds the builtins
+    scopes: Vec<HashMap<String, Binding>>,
+    /// Names reassigned somewhere, their type can change behind our back
+    reassigned: HashSet<String>,
+    /// The return hint of the function being checked
+    return_kind: Option<PrimitiveKind>,
+    /// Index of the first scope of the function being checked
+    activation: usize,
+    /// Conditions have to be Booleans, as with `tammr --strict`
+    strict: bool,
+    methods: MethodRegistry,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Default for Checker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Checker {
+    pub fn new() -> Self {
+        let builtins = builtin::builtins();
+        let methods = builtin::methods(&builtins);
+
+        let globals = builtins
+            .iter()
+            .map(|(name, obj)| (name.clone(), Binding::new(Type::of_object(obj), None)))
+            .collect();
+
+        Self {
+            scopes: vec![globals],
+            reassigned: HashSet::new(),
+            return_kind: None,
+            activation: 1,
+            strict: false,
+            methods,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Reports conditions that are not Booleans, which only strict mode
+    /// rejects. Otherwise any value counts by its truthiness.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    pub fn check(mut self, program: &Program) -> Vec<Diagnostic> {
+        walk(program, &mut |stmt| {
+            if let Statement::ReAssign { name, .. } = stmt {
+                self.reassigned.insert(name.value.clone());
+            }
+        });
+
+        self.check_function_body(program, &[], None);
+        self.diagnostics
+    }
+
+    fn report(&mut self, position: &Position, message: String) {
+        self.diagnostics.push(Diagnostic {
+            message,
+            position: position.clone(),
+        });
+    }
+
+    /// Reports a condition that strict mode would reject.
+    fn check_condition(&mut self, position: &Position, what: &str, ty: &Type) {
+        if self.strict && !matches!(ty, Type::Boolean | Type::Unknown) {
+            self.report(position, format!("{} must be a bool, got {}", what, ty));
+        }
+    }
+
+    /// The binding a name refers to at this point. Bindings of the function
+    /// being checked whose `let` has not run yet are passed over, as the
+    /// runtime would find an outer one or nothing. Functions defined inside
+    /// may use them, they usually run later.
+    fn lookup(&self, name: &str) -> Option<&Binding> {
+        self.scopes
+            .iter()
+            .enumerate()
+            .rev()
+            .filter_map(|(index, scope)| Some((index, scope.get(name)?)))
+            .find(|(index, binding)| binding.declared || *index < self.activation)
+            .map(|(_, binding)| binding)
+    }
+
+    /// Reports a name `lookup` found nothing for.
+    fn report_unknown(&mut self, ident: &Identifier) {
+        let declared_later = self.scopes[self.activation..]
+            .iter()
+            .any(|scope| scope.contains_key(&ident.value));
+
+        let message = if declared_later {
+            format!("`{}` is used before it is declared", ident.value)
+        } else {
+            format!("Unknown identifier `{}`", ident.value)
+        };
+        self.report(&ident.token.position, message);
+    }
+
+    fn check_function_body(
+        &mut self,
+        body: &[Statement],
+        parameters: &[Parameter],
+        return_kind: Option<PrimitiveKind>,
+    ) {
+        let return_kind = std::mem::replace(&mut self.return_kind, return_kind);
+        let activation = std::mem::replace(&mut self.activation, self.scopes.len());
+
+        // Without a `return` the body evaluates to its last expression
+        if let Some((position, ty)) = self.check_scope(body, parameters) {
+            self.check_return_value(&position, &ty);
+        }
+
+        self.return_kind = return_kind;
+        self.activation = activation;
+    }
+
+    fn check_return_value(&mut self, position: &Position, ty: &Type) {
+        if let Some(kind) = self.return_kind.clone() {
+            if !ty.fits(&kind) {
+                self.report(
+                    position,
+                    format!(
+                        "Type mismatch for return value: expected {}, got {}",
+                        kind, ty
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Checks a block with bindings of its own, function bodies and the
+    /// branches of an `if`. Returns what `check_block` does.
+    fn check_scope(
+        &mut self,
+        block: &[Statement],
+        parameters: &[Parameter],
+    ) -> Option<(Position, Type)> {
+        let mut scope = HashMap::new();
+        for stmt in block {
+            let names = match stmt {
+                Statement::Let { name, .. } => vec![name],
+                Statement::Destructure { pattern, .. } => pattern.bindings(),
+                _ => continue,
+            };
+            for name in names {
+                let mut binding = Binding::new(Type::Unknown, None);
+                binding.declared = false;
+                scope.insert(name.value.clone(), binding);
+            }
+        }
+        for parameter in parameters {
+            let binding = Binding::new(
+                parameter.kind.as_ref().map_or(Type::Unknown, Type::of_hint),
+                parameter.kind.clone(),
+            );
+            scope.insert(parameter.name.value.clone(), binding);
+        }
+
+        self.scopes.push(scope);
+        let value = self.check_block(block);
+        self.scopes.pop();
+        value
+    }
+
+    /// Returns the position and type of a trailing expression statement, the
+    /// value the block evaluates to.
+    fn check_block(&mut self, block: &[Statement]) -> Option<(Position, Type)> {
+        let mut value = None;
+        for stmt in block {
+            value = match stmt {
+                Statement::Expression { token, value } => {
+                    Some((token.position.clone(), self.infer(value)))
+                }
+                stmt => {
+                    self.check_statement(stmt);
+                    None
+                }
+            };
+        }
+        value
+    }
+
+    fn check_statement(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Let {
+                name,
+                value,
+                value_kind,
+                constant,
+                ..
+            } => {
+                let redeclared = self
+                    .scopes
+                    .last()
+                    .and_then(|scope| scope.get(&name.value))
+                    .is_some_and(|binding| binding.declared);
+                if redeclared {
+                    self.report(
+                        &name.token.position,
+                        format!("Cannot redeclare `{}`", name.value),
+                    );
+                }
+
+                let ty = self.infer(value);
+
+                if let Some(kind) = value_kind {
+                    if !ty.fits(kind) {
+                        self.report(
+                            &name.token.position,
+                            format!(
+                                "Type mismatch for `{}`: expected {}, got {}",
+                                name.value, kind, ty
+                            ),
+                        );
+                    }
+                }
+
+                let ty = match value_kind {
+                    Some(kind) => Type::of_hint(kind),
+                    None if self.reassigned.contains(&name.value) => Type::Unknown,
+                    None => ty,
+                };
+
+                let mut binding = Binding::new(ty, value_kind.clone());
+                binding.constant = *constant;
+                if let Some(scope) = self.scopes.last_mut() {
+                    scope.insert(name.value.clone(), binding);
+                }
+            }
+            Statement::Destructure {
+                pattern,
+                value,
+                constant,
+                ..
+            } => {
+                self.infer(value);
+
+                for name in pattern.bindings() {
+                    let redeclared = self
+                        .scopes
+                        .last()
+                        .and_then(|scope| scope.get(&name.value))
+                        .is_some_and(|binding| binding.declared);
+                    if redeclared {
+                        self.report(
+                            &name.token.position,
+                            format!("Cannot redeclare `{}`", name.value),
+                        );
+                    }
+
+                    let mut binding = Binding::new(Type::Unknown, None);
+                    binding.constant = *constant;
+                    if let Some(scope) = self.scopes.last_mut() {
+                        scope.insert(name.value.clone(), binding);
+                    }
+                }
+            }
+            Statement::ReAssign { name, value, .. } => {
+                let ty = self.infer(value);
+
+                let binding = self
+                    .lookup(&name.value)
+                    .map(|binding| (binding.constant, binding.hint.clone()));
+
+                match binding {
+                    None => self.report_unknown(name),
+                    Some((true, _)) => self.report(
+                        &name.token.position,
+                        format!("Cannot reassign constant `{}`", name.value),
+                    ),
+                    Some((_, Some(kind))) if !ty.fits(&kind) => self.report(
+                        &name.token.position,
+                        format!(
+                            "Type mismatch for `{}`: expected {}, got {}",
+                            name.value, kind, ty
+                        ),
+                    ),
+                    Some(_) => (),
+                }
+            }
+            Statement::Assign { target, value, .. } => {
+                self.infer(target);
+                self.infer(value);
+            }
+            Statement::Return { token, value } => {
+                let ty = self.infer(value);
+                self.check_return_value(&token.position, &ty);
+            }
+            Statement::Expression { value, .. } => {
+                self.infer(value);
+            }
+        }
+    }
+
+    fn infer(&mut self, expr: &Expression) -> Type {
+        match expr {
+            Expression::Literal(lit) => match lit {
+                Literal::Integer(_) => Type::Integer,
+                Literal::Float(_) => Type::Float,
+                Literal::Boolean(_) => Type::Boolean,
+                Literal::Null => Type::Null,
+                Literal::String(_) => Type::String,
+                Literal::Regex(_) => Type::Regex,
+                Literal::Array(items) => {
+                    for item in items {
+                        self.infer(item);
+                    }
+                    Type::Array
+                }
+                Literal::Hash(pairs) => {
+                    for (key, value) in pairs {
+                        self.infer(key);
+                        self.infer(value);
+                    }
+                    Type::Hash
+                }
+            },
+            Expression::Identifier(ident) => match self.lookup(&ident.value) {
+                Some(binding) => binding.ty.clone(),
+                None => {
+                    self.report_unknown(ident);
+                    Type::Unknown
+                }
+            },
+            Expression::Prefix {
+                token,
+                operator,
+                right,
+            } => {
+                let right = self.infer(right);
+
+                let result = match operator.as_str() {
+                    "!" | "not" => Type::Boolean,
+                    _ if right.is_number() => right.clone(),
+                    _ => Type::Unknown,
+                };
+
+                let valid = match operator.as_str() {
+                    "!" | "not" => !self.strict || matches!(right, Type::Boolean | Type::Unknown),
+                    _ => right.is_number() || !right.is_known(),
+                };
+                if !valid {
+                    self.report(
+                        &token.position,
+                        format!("Cannot apply `{}` to {}", operator, right),
+                    );
+                }
+
+                result
+            }
+            Expression::Infix {
+                token,
+                left,
+                operator,
+                right,
+            } => {
+                let left = self.infer(left);
+                let right = self.infer(right);
+
+                let logical = operator == "and" || operator == "or";
+                let booleans = [&left, &right]
+                    .iter()
+                    .all(|ty| matches!(ty, Type::Boolean | Type::Unknown));
+                if logical && self.strict && !booleans {
+                    self.report(
+                        &token.position,
+                        format!("Cannot apply `{}` to {} and {}", operator, left, right),
+                    );
+                    return Type::Unknown;
+                }
+
+                match infix_type(&left, operator, &right) {
+                    Some(ty) => ty,
+                    None => {
+                        self.report(
+                            &token.position,
+                            format!("Cannot apply `{}` to {} and {}", operator, left, right),
+                        );
+                        Type::Unknown
+                    }
+                }
+            }
+            Expression::If {
+                token,
+                condition,
+                consequence,
+                alternative,
+            } => {
+                let condition = self.infer(condition);
+                self.check_condition(&token.position, "Condition", &condition);
+
+                self.check_scope(consequence, &[]);
+                if let Some(alternative) = alternative {
+                    self.check_scope(alternative, &[]);
+                }
+
+                Type::Unknown
+            }
+            Expression::Match { value, arms, .. } => {
+                self.infer(value);
+
+                for arm in arms {
+                    let scope = arm
+                        .pattern
+                        .bindings()
+                        .into_iter()
+                        .map(|name| (name.value.clone(), Binding::new(Type::Unknown, None)))
+                        .collect();
+                    self.scopes.push(scope);
+
+                    if let Some(guard) = &arm.guard {
+                        let ty = self.infer(guard);
+                        self.check_condition(&arm.token.position, "Match guard", &ty);
+                    }
+                    self.infer(&arm.body);
+
+                    self.scopes.pop();
+                }
+
+                Type::Unknown
+            }
+            Expression::FunctionLiteral {
+                parameters,
+                return_kind,
+                body,
+                ..
+            } => {
+                self.check_function_body(body, parameters, return_kind.clone());
+                Type::Function {
+                    parameters: parameters.iter().map(|param| param.kind.clone()).collect(),
+                    returns: return_kind.clone(),
+                }
+            }
+            Expression::FunctionCall {
+                token,
+                function,
+                arguments,
+            } => {
+                let callee = match function.as_ref() {
+                    Expression::DotNotation { left, right, .. } => {
+                        let receiver = self.infer(left);
+                        self.check_member(&receiver, right, true);
+                        Type::Unknown
+                    }
+                    function => self.infer(function),
+                };
+
+                let argument_types: Vec<Type> =
+                    arguments.iter().map(|arg| self.infer(arg)).collect();
+
+                match callee {
+                    Type::Function { parameters, .. } if parameters.len() != arguments.len() => {
+                        self.report(
+                            &token.position,
+                            format!(
+                                "Wrong number of arguments to `{}`: expected {}, got {}",
+                                function,
+                                parameters.len(),
+                                arguments.len()
+                            ),
+                        );
+                        Type::Unknown
+                    }
+                    Type::Function {
+                        parameters,
+                        returns,
+                    } => {
+                        for (i, (kind, ty)) in parameters.iter().zip(&argument_types).enumerate() {
+                            match kind {
+                                Some(kind) if !ty.fits(kind) => self.report(
+                                    &token.position,
+                                    format!(
+                                        "Type mismatch for argument {} to `{}`: expected {}, got {}",
+                                        i + 1,
+                                        function,
+                                        kind,
+                                        ty
+                                    ),
+                                ),
+                                _ => (),
+                            }
+                        }
+                        returns.as_ref().map_or(Type::Unknown, Type::of_hint)
+                    }
+                    Type::Builtin => match function.as_ref() {
+                        Expression::Identifier(name) => builtin_return_type(&name.value),
+                        _ => Type::Unknown,
+                    },
+                    ty if !ty.is_callable() => {
+                        self.report(&token.position, format!("Cannot call {}", ty));
+                        Type::Unknown
+                    }
+                    _ => Type::Unknown,
+                }
+            }
+            Expression::IndexExpression { left, index, .. } => {
+                self.infer(left);
+                self.infer(index);
+                Type::Unknown
+            }
+            Expression::SliceExpression {
+                left,
+                start,
+                end,
+                step,
+                ..
+            } => {
+                let left = self.infer(left);
+                for bound in [start, end, step].into_iter().flatten() {
+                    self.infer(bound);
+                }
+
+                match left {
+                    Type::Array | Type::String => left,
+                    _ => Type::Unknown,
+                }
+            }
+            Expression::DotNotation { left, right, .. } => {
+                let receiver = self.infer(left);
+                self.check_member(&receiver, right, false);
+                Type::Unknown
+            }
+            Expression::Is { value, .. } => {
+                self.infer(value);
+                Type::Boolean
+            }
+        }
+    }
+
+    /// Reports properties and methods the receiver's type does not have.
+    /// Hash fields are data, so only other types are checked.
+    fn check_member(&mut self, receiver: &Type, member: &Expression, call: bool) {
+        let (Some(type_name), Expression::Identifier(name)) = (receiver.registry_name(), member)
+        else {
+            return;
+        };
+
+        let found = if call {
+            self.methods.method(type_name, &name.value).is_some()
+        } else {
+            self.methods.property(type_name, &name.value).is_some()
+        };
+
+        if !found {
+            let kind = if call { "method" } else { "property" };
+            self.report(
+                &name.token.position,
+                format!("No {} named {} on {}", kind, name.value, type_name),
+            );
+        }
+    }
+}
+
+/// The result of `left operator right`, or `None` if the evaluator would
+/// reject it. Mixing types is an error for every operator.
+fn infix_type(left: &Type, operator: &str, right: &Type) -> Option<Type> {
+    if operator == "??" {
+        return Some(match left {
+            Type::Null => right.clone(),
+            Type::Unknown => Type::Unknown,
+            left => left.clone(),
+        });
+    }
+
+    // Gives back whichever side decided the result
+    if operator == "and" || operator == "or" {
+        return Some(if left == right {
+            left.clone()
+        } else {
+            Type::Unknown
+        });
+    }
+
+    // Any two values can be compared for equality
+    if operator == "==" || operator == "!=" {
+        return Some(Type::Boolean);
+    }
+
+    let comparison = matches!(operator, "<" | ">");
+
+    if !left.is_known() || !right.is_known() {
+        return Some(if comparison {
+            Type::Boolean
+        } else {
+            Type::Unknown
+        });
+    }
+
+    if left.is_number() && right.is_number() {
+        return Some(match (comparison, left, right) {
+            (true, _, _) => Type::Boolean,
+            (_, Type::Integer, Type::Integer) => Type::Integer,
+            (_, Type::Float, _) | (_, _, Type::Float) => Type::Float,
+            _ => Type::Number,
+        });
+    }
+
+    match (left, operator, right) {
+        (Type::String, "+", Type::String) => Some(Type::String),
+        (Type::String, "<" | ">", Type::String)
+        | (Type::Array, "<" | ">", Type::Array)
+        | (Type::Boolean, "<" | ">", Type::Boolean) => Some(Type::Boolean),
+        _ => None,
+    }
+}
+
+/// What the builtin `name` is known to return, the conversions and `type`.
+fn builtin_return_type(name: &str) -> Type {
+    match name {
+        "type" | "str" => Type::String,
+        "int" => Type::Integer,
+        "float" => Type::Float,
+        "bool" => Type::Boolean,
+        _ => Type::Unknown,
+    }
+}
+
+/// Calls `f` with every statement in `stmts`, including those in nested
+/// blocks and function bodies.
+fn walk(stmts: &[Statement], f: &mut impl FnMut(&Statement)) {
+    for stmt in stmts {
+        f(stmt);
+
+        match stmt {
+            Statement::Let { value, .. }
+            | Statement::ReAssign { value, .. }
+            | Statement::Destructure { value, .. }
+            | Statement::Return { value, .. }
+            | Statement::Expression { value, .. } => walk_expression(value, f),
+            Statement::Assign { target, value, .. } => {
+                walk_expression(target, f);
+                walk_expression(value, f);
+            }
+        }
+    }
+}
+
+fn walk_expression(expr: &Expression, f: &mut impl FnMut(&Statement)) {
+    let mut walk_one = |expr: &Expression| walk_expression(expr, f);
+
+    match expr {
+        Expression::Identifier(_) => (),
+        Expression::Literal(Literal::Array(items)) => items.iter().for_each(walk_one),
+        Expression::Literal(Literal::Hash(pairs)) => {
+            for (key, value) in pairs {
+                walk_one(key);
+                walk_one(value);
+            }
+        }
+        Expression::Literal(_) => (),
+        Expression::Prefix { right, .. } | Expression::Is { value: right, .. } => walk_one(right),
+        Expression::Infix { left, right, .. }
+        | Expression::IndexExpression {
+            left, index: right, ..
+        }
+        | Expression::DotNotation { left, right, .. } => {
+            walk_one(left);
+            walk_one(right);
+        }
+        Expression::SliceExpression {
+            left,
+            start,
+            end,
+            step,
+            ..
+        } => {
+            walk_one(left);
+            for bound in [start, end, step].into_iter().flatten() {
+                walk_one(bound);
+            }
+        }
+        Expression::FunctionCall {
+            function,
+            arguments,
+            ..
+        } => {
+            walk_one(function);
+            arguments.iter().for_each(walk_one);
+        }
+        Expression::If {
+            condition,
+            consequence,
+            alternative,
+            ..
+        } => {
+            walk_one(condition);
+            walk(consequence, f);
+            if let Some(alternative) = alternative {
+                walk(alternative, f);
+            }
+        }
+        Expression::Match { value, arms, .. } => {
+            walk_expression(value, f);
+            for arm in arms {
+                if let Some(guard) = &arm.guard {
+                    walk_expression(guard, f);
+                }
+                walk_expression(&arm.body, f);
+            }
+        }
+        Expression::FunctionLiteral { body, .. } => walk(body, f),
+    }
+}
@@ -0,0 +1,6 @@
+#[allow(clippy::module_inception)]
+mod check;
+mod types;
+
+pub use check::{Checker, Diagnostic};
+pub use types::Type;
@@ -0,0 +1,110 @@
+use crate::lexer::PrimitiveKind;
+use crate::object::Object;
+
+/// What the checker knows about the value of an expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Integer,
+    Float,
+    /// An integer or a float, e.g. a variable hinted `number`
+    Number,
+    String,
+    Boolean,
+    Array,
+    Hash,
+    Regex,
+    Null,
+    /// A Tammr function with the hints of its parameters and return value
+    Function {
+        parameters: Vec<Option<PrimitiveKind>>,
+        returns: Option<PrimitiveKind>,
+    },
+    Builtin,
+    Unknown,
+}
+
+impl Type {
+    pub fn of_object(obj: &Object) -> Type {
+        match obj {
+            Object::Integer(_) => Type::Integer,
+            Object::Float(_) => Type::Float,
+            Object::String(_) => Type::String,
+            Object::Boolean(_) => Type::Boolean,
+            Object::Array(_) => Type::Array,
+            Object::Hash(_) => Type::Hash,
+            Object::Regex(_) => Type::Regex,
+            Object::Null => Type::Null,
+            Object::Function {
+                parameters,
+                return_kind,
+                ..
+            } => Type::Function {
+                parameters: parameters.iter().map(|param| param.kind.clone()).collect(),
+                returns: return_kind.clone(),
+            },
+            Object::BuiltinFunction(_) => Type::Builtin,
+            _ => Type::Unknown,
+        }
+    }
+
+    pub fn of_hint(kind: &PrimitiveKind) -> Type {
+        match kind {
+            PrimitiveKind::String => Type::String,
+            PrimitiveKind::Number => Type::Number,
+            PrimitiveKind::Array => Type::Array,
+            PrimitiveKind::Kv => Type::Hash,
+            PrimitiveKind::Boolean => Type::Boolean,
+        }
+    }
+
+    pub fn is_known(&self) -> bool {
+        *self != Type::Unknown
+    }
+
+    pub fn is_number(&self) -> bool {
+        matches!(self, Type::Integer | Type::Float | Type::Number)
+    }
+
+    pub fn is_callable(&self) -> bool {
+        matches!(self, Type::Function { .. } | Type::Builtin | Type::Unknown)
+    }
+
+    /// Whether a value of this type satisfies a `let` hint. Unknown values
+    /// are left to the runtime check.
+    pub fn fits(&self, kind: &PrimitiveKind) -> bool {
+        match kind {
+            PrimitiveKind::Number => self.is_number() || !self.is_known(),
+            _ => *self == Type::of_hint(kind) || !self.is_known(),
+        }
+    }
+
+    /// The name the method registry files this type under.
+    pub fn registry_name(&self) -> Option<&'static str> {
+        match self {
+            Type::Integer => Some("Integer"),
+            Type::Float => Some("Float"),
+            Type::String => Some("String"),
+            Type::Boolean => Some("Boolean"),
+            Type::Array => Some("Array"),
+            Type::Regex => Some("Regex"),
+            _ => None,
+        }
+    }
+}
+
+/// Spelled like the `let` hints, the same way runtime type errors are
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Type::Integer | Type::Float | Type::Number => write!(f, "number"),
+            Type::String => write!(f, "str"),
+            Type::Boolean => write!(f, "bool"),
+            Type::Array => write!(f, "arr"),
+            Type::Hash => write!(f, "kv"),
+            Type::Regex => write!(f, "regex"),
+            Type::Null => write!(f, "null"),
+            Type::Function { .. } | Type::Builtin => write!(f, "function"),
+            Type::Unknown => write!(f, "unknown"),
+        }
+    }
+}
@@ -1,17 +1,34 @@
+use crate::lexer::PrimitiveKind;
 use crate::object::Object;
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq)]
 pub struct Env {
     store: HashMap<String, Object>,
+    /// Type hints of the bindings in `store` that were declared with one
+    hints: HashMap<String, PrimitiveKind>,
+    /// Bindings declared with `const`
+    constants: HashSet<String>,
     parent: Option<Rc<RefCell<Env>>>,
 }
 
+impl Default for Env {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Env {
     pub fn new() -> Self {
         // TODO: If builtins dont work, look at this
         Self {
             store: HashMap::new(),
+            hints: HashMap::new(),
+            constants: HashSet::new(),
             parent: None,
         }
     }
@@ -30,9 +47,66 @@ impl Env {
         self.store.insert(k.to_string(), v);
     }
 
+    /// Creates a binding in this scope, replacing any earlier one of the same
+    /// name along with its hint.
+    pub fn declare(&mut self, k: &str, v: Object, hint: Option<PrimitiveKind>, constant: bool) {
+        match hint {
+            Some(hint) => self.hints.insert(k.to_string(), hint),
+            None => self.hints.remove(k),
+        };
+        if constant {
+            self.constants.insert(k.to_string());
+        } else {
+            self.constants.remove(k);
+        }
+        self.set(k, v);
+    }
+
+    /// Whether `key` is bound in this scope itself, not in a parent.
+    pub fn has_own(&self, key: &str) -> bool {
+        self.store.contains_key(key)
+    }
+
+    /// Updates the binding `key` resolves to in the scope that declared it.
+    /// Returns false if there is none.
+    pub fn assign(&mut self, key: &str, v: Object) -> bool {
+        if self.store.contains_key(key) {
+            self.set(key, v);
+            return true;
+        }
+
+        match &self.parent {
+            Some(parent) => parent.borrow_mut().assign(key, v),
+            None => false,
+        }
+    }
+
+    /// The type hint of the binding `key` resolves to, if it was given one.
+    pub fn hint(&self, key: &str) -> Option<PrimitiveKind> {
+        if self.store.contains_key(key) {
+            return self.hints.get(key).cloned();
+        }
+
+        self.parent.as_ref()?.borrow().hint(key)
+    }
+
+    /// Whether the binding `key` resolves to was declared with `const`.
+    pub fn is_constant(&self, key: &str) -> bool {
+        if self.store.contains_key(key) {
+            return self.constants.contains(key);
+        }
+
+        match &self.parent {
+            Some(parent) => parent.borrow().is_constant(key),
+            None => false,
+        }
+    }
+
     pub fn extend(parent: Rc<RefCell<Env>>) -> Self {
         Self {
             store: HashMap::new(),
+            hints: HashMap::new(),
+            constants: HashSet::new(),
             parent: Some(parent),
         }
     }
@@ -1,27 +1,103 @@
 use crate::ast::{BlockStatement, Program};
+use crate::builtin::{self, Builtin, Context, MethodRegistry, NativeModule};
 use crate::env::Env;
 use crate::object::Object;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 
 pub struct Evaluator {
     pub(crate) env: Rc<RefCell<Env>>,
+    pub(crate) builtins: HashMap<String, Object>,
+    pub(crate) methods: MethodRegistry,
+    /// Warn about `let` statements without a type hint and only accept
+    /// Booleans as conditions
+    pub(crate) strict: bool,
+    pub(crate) warnings: Vec<String>,
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Evaluator {
     pub fn new() -> Self {
+        let builtins = builtin::builtins();
+        let methods = builtin::methods(&builtins);
+
         Self {
             env: Rc::new(RefCell::new(Env::new())),
+            builtins,
+            methods,
+            strict: false,
+            warnings: Vec::new(),
         }
     }
 
+    /// Makes a Rust function callable from scripts under `name`, next to
+    /// `len`, `exec` and the other builtins. Replaces any builtin of that name.
+    pub fn define_builtin(
+        &mut self,
+        name: &str,
+        func: impl Fn(&mut Context, Vec<Object>) -> Object + 'static,
+    ) {
+        self.builtins.insert(
+            name.to_string(),
+            Object::BuiltinFunction(Builtin::new(name, func)),
+        );
+    }
+
+    /// Makes a module of builtins reachable from scripts by its name.
+    pub fn define_module(&mut self, module: NativeModule) {
+        self.builtins
+            .insert(module.name().to_string(), module.into_object());
+    }
+
+    /// Adds a method to every value of a type, named as in error messages
+    /// (`"String"`, `"Array"`, ...). The receiver is the first argument.
+    pub fn define_method(
+        &mut self,
+        type_name: &str,
+        name: &str,
+        func: impl Fn(&mut Context, Vec<Object>) -> Object + 'static,
+    ) {
+        let builtin = Builtin::new(&format!("{}.{}", type_name, name), func);
+        self.methods.define_method(type_name, name, builtin);
+    }
+
+    /// Like `define_method`, for dot access without a call (`arr.length`).
+    pub fn define_property(
+        &mut self,
+        type_name: &str,
+        name: &str,
+        func: impl Fn(&mut Context, Vec<Object>) -> Object + 'static,
+    ) {
+        let builtin = Builtin::new(&format!("{}.{}", type_name, name), func);
+        self.methods.define_property(type_name, name, builtin);
+    }
+
+    /// In strict mode every `let` should carry a type hint, missing ones are
+    /// reported through `take_warnings`, and `if`, `not`, `and`, `or` and
+    /// match guards only accept Booleans instead of any value by its
+    /// truthiness.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Warnings collected since the last call, oldest first.
+    pub fn take_warnings(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.warnings)
+    }
+
     pub fn eval(&mut self, program: &Program) -> Option<Object> {
         let mut result: Option<Object> = None;
 
         for stmt in program {
             match self.eval_statement(stmt) {
                 Some(Object::Return(obj)) => return Some(*obj),
-                Some(Object::Error(msg)) => println!("{}", msg),
+                Some(Object::Error(msg)) => return Some(Object::Error(msg)),
                 Some(obj) => result = Some(obj),
                 None => {
                     return Some(
@@ -44,7 +120,7 @@ impl Evaluator {
         for stmt in stmts {
             match self.eval_statement(&stmt) {
                 Some(Object::Return(obj)) => return Some(Object::Return(obj)),
-                Some(Object::Error(msg)) => println!("{}", msg),
+                Some(Object::Error(msg)) => return Some(Object::Error(msg)),
                 Some(obj) => result = Some(obj),
                 None => {
                     return Some(
@@ -56,4 +132,15 @@ impl Evaluator {
 
         result
     }
+
+    /// Runs a block in a scope of its own, so its bindings end with it.
+    pub(crate) fn eval_scoped_block(&mut self, stmts: BlockStatement) -> Option<Object> {
+        let outer = Rc::clone(&self.env);
+        self.env = Rc::new(RefCell::new(Env::extend(Rc::clone(&outer))));
+
+        let result = self.eval_block_statement(stmts);
+        self.env = outer;
+
+        result
+    }
 }
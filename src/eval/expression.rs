@@ -1,12 +1,12 @@
 use std::cell::RefCell;
+use std::cmp::Ordering;
 use std::rc::Rc;
 
 use super::Evaluator;
-use crate::ast::{Expression, Identifier, Program};
-use crate::builtin::{self, DotBuiltinKind};
+use crate::ast::{Expression, Identifier, Parameter, Program, TypeTest};
 use crate::env::Env;
 use crate::lexer::Token;
-use crate::object::Object;
+use crate::object::{HashKey, Object};
 
 impl Evaluator {
     pub(crate) fn eval_expression(&mut self, value: &Expression) -> Option<Object> {
@@ -31,16 +31,18 @@ impl Evaluator {
             } => self.eval_if_expression(token, condition, consequence, alternative),
             Expression::Identifier(iden) => self.eval_identifier(iden),
             Expression::FunctionCall {
-                token: _,
+                token,
                 function,
                 arguments,
-            } => self.eval_function_call(function, arguments),
+            } => self.eval_function_call(token, function, arguments),
             Expression::FunctionLiteral {
                 token: _,
                 parameters,
+                return_kind,
                 body,
             } => Some(Object::Function {
                 parameters: parameters.clone(),
+                return_kind: return_kind.clone(),
                 body: *body.clone(),
                 env: Rc::clone(&self.env),
             }),
@@ -53,159 +55,337 @@ impl Evaluator {
                 token: _,
                 left,
                 right,
-            } => self.eval_dot_notation(left, right),
+                optional,
+            } => self.eval_dot_notation(left, right, *optional),
+            Expression::Match { token, value, arms } => {
+                self.eval_match_expression(token, value, arms)
+            }
+            Expression::Is { value, test, .. } => {
+                let value = self.eval_expression(value)?;
+                if value.is_error() {
+                    return Some(value);
+                }
+
+                match test {
+                    TypeTest::Null => Some(Object::Boolean(value == Object::Null)),
+                    TypeTest::Kind(kind) => Some(Object::Boolean(value.fits(kind))),
+                }
+            }
+            Expression::SliceExpression {
+                token: _,
+                left,
+                start,
+                end,
+                step,
+            } => self.eval_slice_expression(left, [start, end, step]),
         }
     }
-    fn eval_dot_expr(
+    fn eval_dot_notation(
         &mut self,
-        value: &Expression,
-    ) -> Option<(String, Option<Expression>, Option<Vec<Expression>>)> {
-        match value {
-            Expression::Identifier(iden) => Some((iden.value.clone(), None, None)),
-            Expression::FunctionCall {
-                token,
-                function,
-                arguments,
-            } => Some((
-                token.literal.clone(),
-                Some(*function.clone()),
-                Some(arguments.clone()),
-            )),
-            _ => None,
+        left: &Expression,
+        right: &Expression,
+        optional: bool,
+    ) -> Option<Object> {
+        let left = self.eval_expression(left)?;
+        if optional && left == Object::Null {
+            return Some(Object::Null);
         }
-    }
 
-    fn eval_dot_notation(&mut self, left: &Expression, right: &Expression) -> Option<Object> {
-        let left = self.eval_expression(left);
+        self.eval_dot_access(left, right)
+    }
 
-        if let Some(left) = left {
-            match left {
-                Object::Hash(hash) => {
-                    for (k, v) in hash {
-                        if let Object::String(k) = k {
-                            if k == right.to_string() {
-                                return Some(v);
-                            }
-                        }
-                    }
+    /// `left.right` without a call: a field of a hash, or a property from
+    /// the method registry for everything else.
+    pub(crate) fn eval_dot_access(&mut self, left: Object, right: &Expression) -> Option<Object> {
+        let name = match (&left, right) {
+            (Object::Error(_), _) => return Some(left),
+            (Object::Hash(hash), _) => {
+                return Some(
+                    hash.borrow()
+                        .get(&HashKey::String(right.to_string()))
+                        .cloned()
+                        .unwrap_or(Object::Null),
+                )
+            }
+            (_, Expression::Identifier(name)) => name.value.clone(),
+            _ => return Some(self.new_error("Use dot notation properly")),
+        };
+
+        match self.methods.property(left.type_name(), &name).cloned() {
+            Some(property) => Some(property.call(self, None, vec![left])),
+            None => Some(self.new_error(&format!(
+                "No property named {} on {}",
+                name,
+                left.type_name()
+            ))),
+        }
+    }
 
-                    return Some(Object::Null);
-                }
-                Object::String(string) => {
-                    let right = self.eval_dot_expr(right);
+    /// `left.right(arguments)`. Functions stored in a hash (like `json.parse`)
+    /// come first, then the methods registered for the receiver's type.
+    fn eval_method_call(
+        &mut self,
+        token: &Token,
+        left: &Expression,
+        right: &Expression,
+        optional: bool,
+        arguments: &[Expression],
+    ) -> Option<Object> {
+        let receiver = self.eval_expression(left)?;
+        if receiver.is_error() {
+            return Some(receiver);
+        }
 
-                    if right.is_none() {
-                        return Some(self.new_error("Use dot notation on strings"));
-                    }
+        // `x?.f(...)` skips the call, arguments included
+        if optional && receiver == Object::Null {
+            return Some(Object::Null);
+        }
 
-                    let (name, _func, _args) = right.unwrap();
+        let mut arguments = self.eval_expressions(arguments)?;
+        if let Some(err) = arguments.iter().find(|arg| arg.is_error()) {
+            return Some(err.clone());
+        }
 
-                    // Is property
-                    return builtin::dot_str_builtins(&string, DotBuiltinKind::Property(name));
-                }
-                _ => return Some(self.new_error("Use dot notation properly")),
+        let Expression::Identifier(name) = right else {
+            return Some(self.new_error("Use dot notation properly"));
+        };
+
+        if let Object::Hash(hash) = &receiver {
+            let member = hash
+                .borrow()
+                .get(&HashKey::from(name.value.as_str()))
+                .cloned();
+            if let Some(function) = member {
+                return self.call_function(token, function, arguments);
             }
         }
 
-        None
+        match self
+            .methods
+            .method(receiver.type_name(), &name.value)
+            .cloned()
+        {
+            Some(method) => {
+                arguments.insert(0, receiver);
+                Some(method.call(self, Some(token.position.clone()), arguments))
+            }
+            None => Some(self.new_error(&format!(
+                "No method named {} on {}",
+                name.value,
+                receiver.type_name()
+            ))),
+        }
     }
 
     fn eval_index_expression(&mut self, left: &Expression, index: &Expression) -> Option<Object> {
         let left = self.eval_expression(left);
         let index = self.eval_expression(index);
 
-        if let Some(left) = left {
-            if let Some(index) = index {
-                match (left, index) {
-                    (Object::Array(arr), Object::Integer(int)) => {
-                        if int <= -1 {
-                            if let Some(item) =
-                                arr.iter().nth_back((int.unsigned_abs() - 1) as usize)
-                            {
-                                return Some(item.clone());
-                            }
-                        }
+        Some(self.index_value(left?, index?))
+    }
 
-                        if int >= arr.len() as i64 {
-                            return Some(Object::Null);
-                        }
+    pub(crate) fn index_value(&mut self, left: Object, index: Object) -> Object {
+        match (left, index) {
+            (Object::Error(msg), _) | (_, Object::Error(msg)) => Object::Error(msg),
+            (Object::Array(arr), Object::Integer(int)) => {
+                let arr = arr.borrow();
+
+                if int <= -1 {
+                    return arr
+                        .iter()
+                        .nth_back((int.unsigned_abs() - 1) as usize)
+                        .cloned()
+                        .unwrap_or(Object::Null);
+                }
 
-                        return Some(arr[int as usize].clone());
-                    }
-                    (Object::String(str), Object::Integer(int)) => {
-                        // Is negative, go backwards. i.e -1
-                        if int <= -1 {
-                            if let Some(char) =
-                                str.chars().nth_back((int.unsigned_abs() - 1) as usize)
-                            {
-                                return Some(Object::String(char.to_string()));
-                            }
-                        }
+                if int >= arr.len() as i64 {
+                    return Object::Null;
+                }
 
-                        if int >= str.len() as i64 {
-                            return Some(Object::Null);
-                        }
+                arr[int as usize].clone()
+            }
+            (Object::String(str), Object::Integer(int)) => {
+                // Counts chars, not bytes. Negative goes backwards, i.e -1
+                let char = if int <= -1 {
+                    str.chars().nth_back((int.unsigned_abs() - 1) as usize)
+                } else {
+                    str.chars().nth(int as usize)
+                };
 
-                        if let Some(char) = str.chars().nth(int as usize) {
-                            return Some(Object::String(char.to_string()));
-                        }
-                    }
-                    (Object::Hash(hash), Object::String(key)) => {
-                        for (k, v) in hash {
-                            if let Object::String(k) = k {
-                                if k == key {
-                                    return Some(v);
-                                }
-                            }
-                        }
+                char.map_or(Object::Null, |char| Object::String(char.to_string()))
+            }
+            (Object::Hash(hash), index) => match HashKey::from_object(&index) {
+                Some(key) => hash.borrow().get(&key).cloned().unwrap_or(Object::Null),
+                None => self.new_error(&format!("Unusable as hash key: {}", index.type_name())),
+            },
+            _ => self.new_error("Use index expression on arrays or strings"),
+        }
+    }
 
-                        return Some(Object::Null);
-                    }
-                    _ => return Some(self.new_error("Use index expression on arrays or strings")),
+    fn eval_slice_expression(
+        &mut self,
+        left: &Expression,
+        bounds: [&Option<Box<Expression>>; 3],
+    ) -> Option<Object> {
+        let left = self.eval_expression(left)?;
+        if left.is_error() {
+            return Some(left);
+        }
+
+        let mut values = [None; 3];
+        for (value, bound) in values.iter_mut().zip(bounds) {
+            let Some(bound) = bound else { continue };
+
+            match self.eval_expression(bound)? {
+                Object::Integer(int) => *value = Some(int),
+                err @ Object::Error(_) => return Some(err),
+                other => {
+                    return Some(self.new_error(&format!(
+                        "Slice bounds must be Integers, got {}",
+                        other.type_name()
+                    )))
                 }
             }
         }
 
-        None
+        let [start, end, step] = values;
+        let step = step.unwrap_or(1);
+        if step == 0 {
+            return Some(self.new_error("Slice step must not be zero"));
+        }
+
+        match left {
+            Object::Array(arr) => {
+                let arr = arr.borrow();
+                let positions = slice_positions(arr.len(), start, end, step);
+
+                Some(Object::array(positions.map(|i| arr[i].clone()).collect()))
+            }
+            Object::String(str) => {
+                let chars = str.chars().collect::<Vec<_>>();
+                let positions = slice_positions(chars.len(), start, end, step);
+
+                Some(Object::String(positions.map(|i| chars[i]).collect()))
+            }
+            _ => Some(self.new_error(&format!(
+                "Use slices on arrays or strings, got {}",
+                left.type_name()
+            ))),
+        }
     }
 
     fn eval_function_call(
         &mut self,
+        token: &Token,
         function: &Expression,
         arguments: &[Expression],
     ) -> Option<Object> {
+        if let Expression::DotNotation {
+            left,
+            right,
+            optional,
+            ..
+        } = function
+        {
+            return self.eval_method_call(token, left, right, *optional, arguments);
+        }
+
         let function = self.eval_expression(function)?;
+        if function.is_error() {
+            return Some(function);
+        }
 
         let arguments = self.eval_expressions(arguments)?;
+        if let Some(err) = arguments.iter().find(|arg| arg.is_error()) {
+            return Some(err.clone());
+        }
+
+        self.call_function(token, function, arguments)
+    }
 
+    /// Like `apply_function`, but builtins learn where they were called from.
+    fn call_function(
+        &mut self,
+        token: &Token,
+        function: Object,
+        arguments: Vec<Object>,
+    ) -> Option<Object> {
+        match function {
+            Object::BuiltinFunction(builtin) => {
+                Some(builtin.call(self, Some(token.position.clone()), arguments))
+            }
+            _ => self.apply_function(function, arguments),
+        }
+    }
+
+    pub(crate) fn apply_function(
+        &mut self,
+        function: Object,
+        arguments: Vec<Object>,
+    ) -> Option<Object> {
         match function {
             Object::Function {
-                parameters,
-                body,
-                env,
+                ref parameters,
+                ref return_kind,
+                ref body,
+                ref env,
             } => {
                 if arguments.len() != parameters.len() {
-                    Some(self.new_error(&format!(
+                    return Some(self.new_error(&format!(
                         "Wrong number of arguments. Expected {}, got {}",
                         parameters.len(),
                         arguments.len()
-                    )))
-                } else {
-                    let old_env = Rc::clone(&self.env);
-                    let mut new_env = Env::extend(Rc::clone(&env));
-                    let zipped = parameters.iter().zip(arguments);
-                    for (_, (Identifier { token: _, value }, o)) in zipped.enumerate() {
-                        new_env.set(value, o);
+                    )));
+                }
+
+                let zipped = parameters.iter().zip(&arguments);
+                for (Parameter { name, kind }, argument) in zipped {
+                    if let Some(kind) = kind {
+                        if !argument.fits(kind) {
+                            return Some(self.new_error(&format!(
+                                "Type mismatch for parameter `{}` of {}: expected {}, got {}",
+                                name,
+                                function.type_signature(),
+                                kind,
+                                argument.hint_name()
+                            )));
+                        }
                     }
+                }
 
-                    self.env = Rc::new(RefCell::new(new_env));
-                    let object = self.eval_block_statement(body);
-                    self.env = old_env;
+                let old_env = Rc::clone(&self.env);
+                let mut new_env = Env::extend(Rc::clone(env));
+                for (Parameter { name, kind }, argument) in parameters.iter().zip(arguments) {
+                    new_env.declare(&name.value, argument, kind.clone(), false);
+                }
 
-                    object
+                self.env = Rc::new(RefCell::new(new_env));
+                let object = self.eval_block_statement(body.clone());
+                self.env = old_env;
+
+                let object = match object {
+                    Some(Object::Return(obj)) => Some(*obj),
+                    object => object,
+                };
+
+                match (return_kind, &object) {
+                    (_, Some(Object::Error(_))) | (None, _) => object,
+                    (Some(kind), _) => {
+                        let value = object.unwrap_or(Object::Null);
+                        if value.fits(kind) {
+                            Some(value)
+                        } else {
+                            Some(self.new_error(&format!(
+                                "Type mismatch for return value of {}: expected {}, got {}",
+                                function.type_signature(),
+                                kind,
+                                value.hint_name()
+                            )))
+                        }
+                    }
                 }
             }
-            Object::BuiltinFunction(func) => Some(func(arguments)),
+            Object::BuiltinFunction(builtin) => Some(builtin.call(self, None, arguments)),
             _ => Some(self.new_error(&format!("Not a function: {}", function))),
         }
     }
@@ -226,8 +406,8 @@ impl Evaluator {
             return Some(value);
         }
 
-        if builtin::builtins().contains_key(&iden.value) {
-            return Some(builtin::builtins()[&iden.value].clone());
+        if let Some(builtin) = self.builtins.get(&iden.value) {
+            return Some(builtin.clone());
         }
 
         Some(self.new_error(&format!(
@@ -244,21 +424,41 @@ impl Evaluator {
         alternative: &Option<Box<Program>>,
     ) -> Option<Object> {
         let condition = self.eval_expression(condition)?;
+        if condition.is_error() {
+            return Some(condition);
+        }
 
-        match condition {
-            Object::Boolean(bool) => {
-                if bool {
-                    self.eval_block_statement(consequence.to_vec())
-                } else if let Some(alt) = alternative {
-                    self.eval_block_statement(alt.to_vec())
-                } else {
-                    Some(Object::Null)
-                }
-            }
-            _ => Some(self.new_error("Use if conditionals on booleans")),
+        match self.condition(&condition) {
+            Some(true) => self.eval_scoped_block(consequence.to_vec()),
+            Some(false) => match alternative {
+                Some(alt) => self.eval_scoped_block(alt.to_vec()),
+                None => Some(Object::Null),
+            },
+            None => Some(self.new_error("Use if conditionals on booleans")),
+        }
+    }
+
+    /// Whether a value used as a condition holds, by its truthiness or, in
+    /// strict mode, only for Booleans. `None` for other values in strict mode.
+    pub(crate) fn condition(&self, value: &Object) -> Option<bool> {
+        match value {
+            Object::Boolean(bool) => Some(*bool),
+            _ if self.strict => None,
+            value => Some(value.is_truthy()),
         }
     }
 
+    /// The right side of an `and` or `or` the left side did not decide,
+    /// which has to be a Boolean too in strict mode.
+    fn eval_logical_right(&mut self, operator: &str, right: &Expression) -> Option<Object> {
+        let right = self.eval_expression(right)?;
+        if right.is_error() || self.condition(&right).is_some() {
+            return Some(right);
+        }
+
+        Some(self.new_error(&format!("Use {} operator on booleans", operator)))
+    }
+
     fn eval_infix_expression(
         &mut self,
         left: &Expression,
@@ -266,89 +466,180 @@ impl Evaluator {
         right: &Expression,
     ) -> Option<Object> {
         let left = self.eval_expression(left)?;
+        if left.is_error() {
+            return Some(left);
+        }
+
+        // Only falls back to the right side when it is needed
+        if operator == "??" {
+            return match left {
+                Object::Null => self.eval_expression(right),
+                left => Some(left),
+            };
+        }
+
+        // `and` and `or` give back whichever side decided the result
+        if operator == "and" || operator == "or" {
+            return match self.condition(&left) {
+                Some(holds) if holds == (operator == "or") => Some(left),
+                Some(_) => self.eval_logical_right(operator, right),
+                None => Some(self.new_error(&format!("Use {} operator on booleans", operator))),
+            };
+        }
+
         let right = self.eval_expression(right)?;
+        if right.is_error() {
+            return Some(right);
+        }
+
+        if operator == "==" || operator == "!=" {
+            return Some(match left.equals(&right) {
+                Ok(equal) => Object::Boolean(equal == (operator == "==")),
+                Err(msg) => self.new_error(&msg),
+            });
+        }
 
         match (right, left) {
             (Object::Integer(right_value), Object::Integer(left_value)) => {
                 self.eval_integer_infix_expression(&left_value, operator, &right_value)
             }
-            (Object::Boolean(right_value), Object::Boolean(left_value)) => {
-                self.eval_boolean_infix_expression(&left_value, operator, &right_value)
+            (Object::Float(right_value), Object::Float(left_value)) => {
+                self.eval_float_infix_expression(&left_value, operator, &right_value)
             }
-            (Object::String(right_value), Object::String(left_value)) => {
-                self.eval_string_infix_expression(&left_value, operator, &right_value)
+            (Object::Float(right_value), Object::Integer(left_value)) => {
+                self.eval_float_infix_expression(&(left_value as f64), operator, &right_value)
             }
-            _ => Some(self.new_error("Use infix operators on integers")),
-        }
-    }
+            (Object::Integer(right_value), Object::Float(left_value)) => {
+                self.eval_float_infix_expression(&left_value, operator, &(right_value as f64))
+            }
+            (Object::String(right_value), Object::String(left_value)) if operator == "+" => {
+                Some(Object::String(format!("{}{}", left_value, right_value)))
+            }
+            (right, left) if operator == "<" || operator == ">" => {
+                let expected = if operator == "<" {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                };
 
-    fn eval_string_infix_expression(
-        &mut self,
-        left: &str,
-        operator: &str,
-        right: &str,
-    ) -> Option<Object> {
-        match operator {
-            "+" => Some(Object::String(format!("{}{}", left, right))),
-            "==" => Some(Object::Boolean(left == right)),
-            "!=" => Some(Object::Boolean(left != right)),
-            _ => Some(self.new_error(&format!("Invalid operator: {}", operator))),
+                match left.compare(&right) {
+                    Ok(ordering) => Some(Object::Boolean(ordering == expected)),
+                    Err(msg) => Some(self.new_error(&msg)),
+                }
+            }
+            _ => Some(self.new_error("Use infix operators on integers")),
         }
     }
 
-    fn eval_boolean_infix_expression(
+    fn eval_integer_infix_expression(
         &mut self,
-        left: &bool,
+        left: &i64,
         operator: &str,
-        right: &bool,
+        right: &i64,
     ) -> Option<Object> {
-        match operator {
-            "==" => Some(Object::Boolean(left == right)),
-            "!=" => Some(Object::Boolean(left != right)),
-            _ => Some(self.new_error(&format!("Invalid operator: {}", operator))),
+        let result = match operator {
+            "+" => left.checked_add(*right),
+            "-" => left.checked_sub(*right),
+            "*" => left.checked_mul(*right),
+            "/" if *right == 0 => return Some(self.new_error("Division by zero")),
+            "/" => left.checked_div(*right),
+            "<" => return Some(Object::Boolean(left < right)),
+            ">" => return Some(Object::Boolean(left > right)),
+            _ => return Some(self.new_error(&format!("Invalid operator: {}", operator))),
+        };
+
+        match result {
+            Some(int) => Some(Object::Integer(int)),
+            None => Some(self.new_error(&format!(
+                "Integer overflow: {} {} {}",
+                left, operator, right
+            ))),
         }
     }
 
-    fn eval_integer_infix_expression(
+    fn eval_float_infix_expression(
         &mut self,
-        left: &i64,
+        left: &f64,
         operator: &str,
-        right: &i64,
+        right: &f64,
     ) -> Option<Object> {
         match operator {
-            "+" => Some(Object::Integer(left + right)),
-            "-" => Some(Object::Integer(left - right)),
-            "*" => Some(Object::Integer(left * right)),
-            "/" => Some(Object::Integer(left / right)),
+            "+" => Some(Object::Float(left + right)),
+            "-" => Some(Object::Float(left - right)),
+            "*" => Some(Object::Float(left * right)),
+            "/" => Some(Object::Float(left / right)),
             "<" => Some(Object::Boolean(left < right)),
             ">" => Some(Object::Boolean(left > right)),
-            "==" => Some(Object::Boolean(left == right)),
-            "!=" => Some(Object::Boolean(left != right)),
             _ => Some(self.new_error(&format!("Invalid operator: {}", operator))),
         }
     }
 
     fn eval_prefix_expression(&mut self, operator: &str, right: &Expression) -> Option<Object> {
         let right = self.eval_expression(right)?;
+        if right.is_error() {
+            return Some(right);
+        }
 
         match operator {
-            "!" => self.eval_bang_prefix(right),
+            "!" | "not" => self.eval_bang_prefix(operator, right),
             "-" => self.eval_minus_prefix(right),
             _ => Some(self.new_error("Invalid prefix operator")),
         }
     }
 
-    fn eval_bang_prefix(&mut self, right: Object) -> Option<Object> {
-        match right {
-            Object::Boolean(bool) => Some(Object::Boolean(!bool)),
-            _ => Some(self.new_error("Use ! prefix operator on booleans!")),
+    fn eval_bang_prefix(&mut self, operator: &str, right: Object) -> Option<Object> {
+        match self.condition(&right) {
+            Some(holds) => Some(Object::Boolean(!holds)),
+            None => Some(self.new_error(&format!("Use {} prefix operator on booleans!", operator))),
         }
     }
 
     fn eval_minus_prefix(&mut self, right: Object) -> Option<Object> {
         match right {
-            Object::Integer(int) => Some(Object::Integer(-int)),
+            Object::Integer(int) => match int.checked_neg() {
+                Some(int) => Some(Object::Integer(int)),
+                None => Some(self.new_error(&format!("Integer overflow: -({})", int))),
+            },
+            Object::Float(float) => Some(Object::Float(-float)),
             _ => Some(self.new_error("Use - prefix operator on integers or floats")),
         }
     }
 }
+
+/// The positions a slice picks out of `len` items, the way Python does it:
+/// negative bounds count from the end, out of range ones are clamped, and a
+/// negative step walks backwards from the end by default.
+fn slice_positions(
+    len: usize,
+    start: Option<i64>,
+    end: Option<i64>,
+    step: i64,
+) -> impl Iterator<Item = usize> {
+    let len = len as i64;
+    let (lower, upper) = if step > 0 { (0, len) } else { (-1, len - 1) };
+
+    let resolve = |bound: Option<i64>, default: i64| match bound {
+        None => default,
+        Some(bound) if bound < 0 => (bound + len).max(lower),
+        Some(bound) => bound.min(upper),
+    };
+
+    // `None` once stepping past the end would overflow
+    let mut i = Some(resolve(start, if step > 0 { lower } else { upper }));
+    let end = resolve(end, if step > 0 { upper } else { lower });
+
+    std::iter::from_fn(move || {
+        let current = i?;
+        let next = if step > 0 {
+            current < end
+        } else {
+            current > end
+        };
+        if !next {
+            return None;
+        }
+
+        i = current.checked_add(step);
+        Some(current as usize)
+    })
+}
@@ -1,13 +1,23 @@
+use std::rc::Rc;
+
+use regex::Regex;
+
 use super::Evaluator;
 use crate::ast::{Expression, Literal};
-use crate::object::Object;
+use crate::object::{HashKey, Object, OrderedHash};
 
 impl Evaluator {
     pub(crate) fn eval_literal(&mut self, lit: &Literal) -> Option<Object> {
         match lit {
             Literal::Integer(int) => Some(Object::Integer(*int)),
+            Literal::Float(float) => Some(Object::Float(*float)),
             Literal::Boolean(bool) => Some(Object::Boolean(*bool)),
+            Literal::Null => Some(Object::Null),
             Literal::String(string) => Some(Object::String(string.clone())),
+            Literal::Regex(pattern) => match Regex::new(pattern) {
+                Ok(regex) => Some(Object::Regex(Rc::new(regex))),
+                Err(err) => Some(self.new_error(&format!("Invalid regex `{}`: {}", pattern, err))),
+            },
             Literal::Array(array) => {
                 let mut result = Vec::new();
 
@@ -16,28 +26,37 @@ impl Evaluator {
                     result.push(evaluated);
                 }
 
-                Some(Object::Array(result))
+                Some(Object::array(result))
             }
             Literal::Hash(pairs) => self.eval_hash_literal(pairs.to_vec()),
         }
     }
 
     fn eval_hash_literal(&mut self, pairs: Vec<(Expression, Expression)>) -> Option<Object> {
-        let mut hash: Vec<(Object, Object)> = Vec::new();
+        let mut hash = OrderedHash::new();
 
         for (k, v) in pairs {
             let key = self.eval_expression(&k)?;
 
-            match key {
-                Object::String(_) => {}
-                _ => return Some(self.new_error("Hash keys must be strings")),
+            let key = match HashKey::from_object(&key) {
+                Some(key) => key,
+                None if key.is_error() => return Some(key),
+                None => {
+                    return Some(
+                        self.new_error(&format!("Unusable as hash key: {}", key.type_name())),
+                    )
+                }
             };
 
             let value = self.eval_expression(&v)?;
+            if value.is_error() {
+                return Some(value);
+            }
 
-            hash.push((key, value));
+            // Repeated keys keep their first position and the last value
+            hash.insert(key, value);
         }
 
-        Some(Object::Hash(hash))
+        Some(Object::hash(hash))
     }
 }
@@ -1,6 +1,8 @@
+#[allow(clippy::module_inception)]
 mod eval;
 mod expression;
 mod literal;
+mod pattern;
 mod statement;
 
 pub use eval::Evaluator;
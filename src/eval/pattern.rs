@@ -0,0 +1,199 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::Evaluator;
+use crate::ast::{Expression, Literal, MatchArm, Pattern};
+use crate::env::Env;
+use crate::lexer::Token;
+use crate::object::{HashKey, Object};
+
+impl Evaluator {
+    /// Runs the first arm whose pattern fits and whose guard holds. The names
+    /// a pattern binds only live in its arm.
+    pub(crate) fn eval_match_expression(
+        &mut self,
+        token: &Token,
+        value: &Expression,
+        arms: &[MatchArm],
+    ) -> Option<Object> {
+        let value = self.eval_expression(value)?;
+        if value.is_error() {
+            return Some(value);
+        }
+
+        for arm in arms {
+            let mut bindings = Vec::new();
+            if !match_pattern(&arm.pattern, &value, &mut bindings) {
+                continue;
+            }
+
+            let outer = Rc::clone(&self.env);
+            let mut env = Env::extend(Rc::clone(&outer));
+            for (name, value) in bindings {
+                env.declare(&name, value, None, false);
+            }
+            self.env = Rc::new(RefCell::new(env));
+
+            let result = match self.guard_holds(arm) {
+                Ok(true) => self.eval_expression(&arm.body),
+                Ok(false) => None,
+                Err(err) => Some(err),
+            };
+            self.env = outer;
+
+            if result.is_some() {
+                return result;
+            }
+        }
+
+        Some(self.new_error(&format!(
+            "No match arm for {} at line {}",
+            value,
+            token.position.line + 1
+        )))
+    }
+
+    /// Whether the arm has no guard or its guard holds, the same way an `if`
+    /// condition does.
+    fn guard_holds(&mut self, arm: &MatchArm) -> Result<bool, Object> {
+        let Some(guard) = &arm.guard else {
+            return Ok(true);
+        };
+
+        let value = self.eval_expression(guard).unwrap_or(Object::Empty);
+        if value.is_error() {
+            return Err(value);
+        }
+
+        match self.condition(&value) {
+            Some(holds) => Ok(holds),
+            None => Err(self.new_error(&format!(
+                "Match guard must be a Boolean, got {} at line {}",
+                value.type_name(),
+                arm.token.position.line + 1
+            ))),
+        }
+    }
+
+    pub(crate) fn eval_destructure(
+        &mut self,
+        token: &Token,
+        pattern: &Pattern,
+        value: &Expression,
+        constant: bool,
+    ) -> Option<Object> {
+        for name in pattern.bindings() {
+            if self.env.borrow().has_own(&name.value) {
+                return Some(self.new_error(&format!(
+                    "Cannot redeclare `{}` at line {}",
+                    name.value,
+                    name.token.position.line + 1
+                )));
+            }
+        }
+
+        let value = self.eval_expression(value)?;
+        if value.is_error() {
+            return Some(value);
+        }
+
+        let mut bindings = Vec::new();
+        if !match_pattern(pattern, &value, &mut bindings) {
+            return Some(self.new_error(&format!(
+                "Cannot destructure {} with {} at line {}",
+                value,
+                pattern,
+                token.position.line + 1
+            )));
+        }
+
+        let mut env = self.env.borrow_mut();
+        for (name, value) in bindings {
+            env.declare(&name, value, None, constant);
+        }
+
+        Some(Object::Empty)
+    }
+}
+
+/// Whether `value` fits `pattern`, adding the names it binds to `bindings`.
+fn match_pattern(pattern: &Pattern, value: &Object, bindings: &mut Vec<(String, Object)>) -> bool {
+    match pattern {
+        Pattern::Wildcard => true,
+        Pattern::Binding(name) => {
+            bindings.push((name.value.clone(), value.clone()));
+            true
+        }
+        Pattern::Value(literal) => match (literal, value) {
+            (Literal::String(a), Object::String(b)) => a == b,
+            (Literal::Boolean(a), Object::Boolean(b)) => a == b,
+            (Literal::Null, Object::Null) => true,
+            (literal, value) => match (number(literal), value) {
+                (Some(a), Object::Integer(b)) => a == *b as f64,
+                (Some(a), Object::Float(b)) => a == *b,
+                _ => false,
+            },
+        },
+        Pattern::Range(start, end) => {
+            let value = match value {
+                Object::Integer(int) => *int as f64,
+                Object::Float(float) => *float,
+                _ => return false,
+            };
+
+            match (number(start), number(end)) {
+                (Some(start), Some(end)) => start <= value && value <= end,
+                _ => false,
+            }
+        }
+        Pattern::Array { items, rest } => {
+            let Object::Array(array) = value else {
+                return false;
+            };
+            let array = array.borrow();
+
+            let fits = match rest {
+                Some(_) => array.len() >= items.len(),
+                None => array.len() == items.len(),
+            };
+            if !fits {
+                return false;
+            }
+
+            for (item, value) in items.iter().zip(array.iter()) {
+                if !match_pattern(item, value, bindings) {
+                    return false;
+                }
+            }
+
+            match rest {
+                Some(rest) => {
+                    let remaining = Object::array(array[items.len()..].to_vec());
+                    match_pattern(rest, &remaining, bindings)
+                }
+                None => true,
+            }
+        }
+        Pattern::Hash(entries) => {
+            let Object::Hash(hash) = value else {
+                return false;
+            };
+            let hash = hash.borrow();
+
+            entries.iter().all(
+                |(key, pattern)| match hash.get(&HashKey::from(key.as_str())) {
+                    Some(value) => match_pattern(pattern, value, bindings),
+                    None => false,
+                },
+            )
+        }
+    }
+}
+
+fn number(literal: &Literal) -> Option<f64> {
+    match literal {
+        Literal::Integer(int) => Some(*int as f64),
+        Literal::Float(float) => Some(*float),
+        _ => None,
+    }
+}
@@ -1,6 +1,7 @@
 use super::Evaluator;
 use crate::ast::{Expression, Identifier, Statement};
-use crate::object::Object;
+use crate::lexer::PrimitiveKind;
+use crate::object::{HashKey, Object};
 
 impl Evaluator {
     pub(crate) fn eval_statement(&mut self, stmt: &Statement) -> Option<Object> {
@@ -11,29 +12,205 @@ impl Evaluator {
                 token: _,
                 name,
                 value,
-                value_kind: _,
+                value_kind,
+                constant,
             } => {
+                if self.env.borrow().has_own(&name.value) {
+                    return Some(self.new_error(&format!(
+                        "Cannot redeclare `{}` at line {}",
+                        name.value,
+                        name.token.position.line + 1
+                    )));
+                }
+
                 let value = self.eval_expression(value)?;
-                self.env.borrow_mut().set(&name.value, value);
+                if value.is_error() {
+                    return Some(value);
+                }
+
+                match value_kind {
+                    Some(kind) => {
+                        if let Some(err) = self.check_hint(name, kind, &value) {
+                            return Some(err);
+                        }
+                    }
+                    None if self.strict => self.warnings.push(format!(
+                        "Missing type hint for `{}` at line {}",
+                        name.value,
+                        name.token.position.line + 1
+                    )),
+                    None => (),
+                }
+
+                self.env
+                    .borrow_mut()
+                    .declare(&name.value, value, value_kind.clone(), *constant);
                 Some(Object::Empty)
             }
+            Statement::Destructure {
+                token,
+                pattern,
+                value,
+                constant,
+            } => self.eval_destructure(token, pattern, value, *constant),
             Statement::ReAssign {
                 token: _,
                 name,
                 value,
             } => self.eval_reassign(name, value),
+            Statement::Assign {
+                token: _,
+                target,
+                value,
+            } => self.eval_assign(target, value),
+        }
+    }
+
+    fn eval_assign(&mut self, target: &Expression, value: &Expression) -> Option<Object> {
+        let value = self.eval_expression(value)?;
+        if value.is_error() {
+            return Some(value);
+        }
+
+        // Collections are shared, so writing into the container is enough
+        let (container, key) = match target {
+            Expression::IndexExpression { left, index, .. } => {
+                (self.eval_assign_path(left)?, self.eval_expression(index)?)
+            }
+            Expression::DotNotation {
+                left,
+                right,
+                optional: false,
+                ..
+            } => match right.as_ref() {
+                Expression::Identifier(field) => (
+                    self.eval_assign_path(left)?,
+                    Object::String(field.value.clone()),
+                ),
+                _ => return Some(self.new_error(&format!("Cannot assign to {}", target))),
+            },
+            _ => return Some(self.new_error(&format!("Cannot assign to {}", target))),
+        };
+
+        match (container, key) {
+            (Object::Error(msg), _) | (_, Object::Error(msg)) => Some(Object::Error(msg)),
+            (Object::Array(items), Object::Integer(index)) => {
+                let mut items = items.borrow_mut();
+                let len = items.len();
+                let position = if index < 0 {
+                    len.checked_sub(index.unsigned_abs() as usize)
+                } else {
+                    Some(index as usize).filter(|&i| i < len)
+                };
+
+                match position {
+                    Some(i) => {
+                        items[i] = value;
+                        Some(Object::Empty)
+                    }
+                    None => Some(
+                        self.new_error(&format!("Index out of range: {} (length {})", index, len)),
+                    ),
+                }
+            }
+            (Object::Array(_), index) => Some(self.new_error(&format!(
+                "Array index must be an Integer, got {}",
+                index.type_name()
+            ))),
+            (Object::Hash(hash), key) => match HashKey::from_object(&key) {
+                Some(key) => {
+                    hash.borrow_mut().insert(key, value);
+                    Some(Object::Empty)
+                }
+                None => Some(self.new_error(&format!("Unusable as hash key: {}", key.type_name()))),
+            },
+            (container, _) => {
+                Some(self.new_error(&format!("Cannot assign into {}", container.type_name())))
+            }
+        }
+    }
+
+    /// Evaluates the containers leading up to an assignment target, so
+    /// `cfg.server.port = 80` reports the missing `server` key instead of
+    /// failing to assign into `null`.
+    fn eval_assign_path(&mut self, path: &Expression) -> Option<Object> {
+        let (container, key) = match path {
+            Expression::IndexExpression { left, index, .. } => {
+                (self.eval_assign_path(left)?, self.eval_expression(index)?)
+            }
+            Expression::DotNotation {
+                left,
+                right,
+                optional: false,
+                ..
+            } => {
+                let container = self.eval_assign_path(left)?;
+                if !matches!(container, Object::Hash(_)) {
+                    return self.eval_dot_access(container, right);
+                }
+                (container, Object::String(right.to_string()))
+            }
+            _ => return self.eval_expression(path),
+        };
+
+        if let (Object::Hash(hash), Some(hash_key)) = (&container, HashKey::from_object(&key)) {
+            if !hash.borrow().contains_key(&hash_key) {
+                return Some(self.new_error(&format!("Key not found: {}", hash_key)));
+            }
         }
+
+        Some(self.index_value(container, key))
     }
 
     fn eval_reassign(&mut self, name: &Identifier, value: &Expression) -> Option<Object> {
         let value = self.eval_expression(value)?;
+        if value.is_error() {
+            return Some(value);
+        }
+
+        if self.env.borrow().get(&name.value).is_none() {
+            return Some(self.new_error(&format!("Identifier not found: {}", name.value)));
+        }
+
+        if self.env.borrow().is_constant(&name.value) {
+            return Some(self.new_error(&format!(
+                "Cannot reassign constant `{}` at line {}",
+                name.value,
+                name.token.position.line + 1
+            )));
+        }
+
+        let hint = self.env.borrow().hint(&name.value);
+        if let Some(kind) = hint {
+            if let Some(err) = self.check_hint(name, &kind, &value) {
+                return Some(err);
+            }
+        }
+
+        // Updates the variable where it was declared, so functions and blocks
+        // can change variables of the scopes around them
+        self.env.borrow_mut().assign(&name.value, value);
+        Some(Object::Empty)
+    }
 
-        if self.env.borrow_mut().get(&name.value).is_some() {
-            self.env.borrow_mut().set(&name.value, value);
-            return Some(Object::Empty);
+    /// An error if `value` does not fit the type hint of `name`.
+    fn check_hint(
+        &self,
+        name: &Identifier,
+        kind: &PrimitiveKind,
+        value: &Object,
+    ) -> Option<Object> {
+        if value.fits(kind) {
+            return None;
         }
 
-        Some(self.new_error(&format!("Identifier not found: {}", name.value)))
+        Some(self.new_error(&format!(
+            "Type mismatch for `{}`: expected {}, got {} at line {}",
+            name.value,
+            kind,
+            value.hint_name(),
+            name.token.position.line + 1
+        )))
     }
 
     fn eval_return(&mut self, value: &Expression) -> Option<Object> {
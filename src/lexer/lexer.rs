@@ -9,15 +9,16 @@ pub struct Lexer {
 }
 
 const KEYWORDS: &[&str] = &[
-    "let", "function", "return", "if", "else", "do", "end", "loop", "exit", "true", "false",
-    "null", "try", "catch", "throw", "and", "or", "not", "is", "import", "as", "foreach", "from",
-    "to", "str", "number", "kv", "arr", "bool",
+    "let", "const", "function", "return", "if", "else", "match", "do", "end", "loop", "exit",
+    "true", "false", "null", "try", "catch", "throw", "and", "or", "not", "is", "import", "as",
+    "foreach", "from", "to", "str", "number", "kv", "arr", "bool",
 ];
 
 #[derive(Debug)]
 pub enum LexerError {
     UnexpectedCharacter(char, Position),
     UnterminatedString(Position),
+    UnterminatedRegex(Position),
     // Add more error types as needed
 }
 
@@ -28,6 +29,7 @@ impl fmt::Display for LexerError {
                 write!(f, "Unexpected character '{}' at {:?}", c, pos)
             }
             LexerError::UnterminatedString(pos) => write!(f, "Unterminated string at {:?}", pos),
+            LexerError::UnterminatedRegex(pos) => write!(f, "Unterminated regex at {:?}", pos),
         }
     }
 }
@@ -51,7 +53,7 @@ impl Lexer {
                 continue;
             }
 
-            if self.current.is_alphabetic() {
+            if self.current.is_alphabetic() || self.current == '_' {
                 tokens.push(self.gen_ident());
                 continue;
             }
@@ -61,6 +63,15 @@ impl Lexer {
                 continue;
             }
 
+            // A `/` where an operand is expected starts a regex, not a division
+            if self.current == '/'
+                && !matches!(self.peek(), '/' | '*')
+                && !Self::ends_operand(tokens.last())
+            {
+                tokens.push(self.gen_regex()?);
+                continue;
+            }
+
             match self.tokenize_single() {
                 Ok(Some(token)) => tokens.push(token),
                 Ok(None) => {} // Comment case, just continue
@@ -97,21 +108,45 @@ impl Lexer {
                 literal: String::from("+"),
                 position: self.position.clone(),
             })),
-            '-' => Ok(Some(Token {
-                ttype: TokenType::Sub,
-                literal: String::from("-"),
-                position: self.position.clone(),
-            })),
+            '-' => {
+                if self.peek() == '>' {
+                    self.advance();
+                    return Ok(Some(Token {
+                        ttype: TokenType::Arrow,
+                        literal: String::from("->"),
+                        position: self.position.clone(),
+                    }));
+                }
+
+                Ok(Some(Token {
+                    ttype: TokenType::Sub,
+                    literal: String::from("-"),
+                    position: self.position.clone(),
+                }))
+            }
             '*' => Ok(Some(Token {
                 ttype: TokenType::Mul,
                 literal: String::from("*"),
                 position: self.position.clone(),
             })),
-            '.' => Ok(Some(Token {
-                ttype: TokenType::Period,
-                literal: String::from("."),
-                position: self.position.clone(),
-            })),
+            '.' => {
+                if self.peek() == '.' && self.src.chars().nth(self.position.index + 2) == Some('.')
+                {
+                    self.advance();
+                    self.advance();
+                    return Ok(Some(Token {
+                        ttype: TokenType::Ellipsis,
+                        literal: String::from("..."),
+                        position: self.position.clone(),
+                    }));
+                }
+
+                Ok(Some(Token {
+                    ttype: TokenType::Period,
+                    literal: String::from("."),
+                    position: self.position.clone(),
+                }))
+            }
             '/' => {
                 if self.peek() == '/' {
                     self.skip_single_line_comment();
@@ -209,10 +244,29 @@ impl Lexer {
                 literal: String::from(":"),
                 position: self.position.clone(),
             })),
+            '?' => {
+                let (ttype, literal) = match self.peek() {
+                    '.' => (TokenType::QuestionDot, "?."),
+                    '?' => (TokenType::Coalesce, "??"),
+                    _ => {
+                        return Err(LexerError::UnexpectedCharacter(
+                            self.current,
+                            self.position.clone(),
+                        ))
+                    }
+                };
+
+                self.advance();
+                Ok(Some(Token {
+                    ttype,
+                    literal: String::from(literal),
+                    position: self.position.clone(),
+                }))
+            }
             // '"' | '\'' => self.gen_string(),
             '"' | '\'' => {
                 let mut string = String::new();
-                let starting_quote_type = self.current.clone();
+                let starting_quote_type = self.current;
                 self.advance();
 
                 while self.current != starting_quote_type {
@@ -260,11 +314,13 @@ impl Lexer {
         if KEYWORDS.contains(&ident.as_str()) {
             let keyword = match ident.as_str() {
                 "let" => KeywordType::Let,
+                "const" => KeywordType::Const,
                 "return" => KeywordType::Return,
                 "true" => KeywordType::True,
                 "false" => KeywordType::False,
                 "if" => KeywordType::If,
                 "else" => KeywordType::Else,
+                "match" => KeywordType::Match,
                 "function" => KeywordType::Fn, // Changed from "fn" to "function"
                 "do" => KeywordType::Do,
                 "end" => KeywordType::End,
@@ -305,6 +361,72 @@ impl Lexer {
         }
     }
 
+    fn ends_operand(token: Option<&Token>) -> bool {
+        matches!(
+            token.map(|token| &token.ttype),
+            Some(
+                TokenType::Ident
+                    | TokenType::Number
+                    | TokenType::String
+                    | TokenType::Regex
+                    | TokenType::RParen
+                    | TokenType::RBracket
+                    | TokenType::RBrace
+                    | TokenType::Keyword(KeywordType::True)
+                    | TokenType::Keyword(KeywordType::False)
+                    | TokenType::Keyword(KeywordType::Null)
+            )
+        )
+    }
+
+    /// `/pattern/flags`. Escapes are left to the regex engine, except `\/`
+    /// for a slash. Flags become an inline group, `/a/i` is `(?i)a`.
+    fn gen_regex(&mut self) -> Result<Token, LexerError> {
+        let start_position = self.position.clone();
+        let mut pattern = String::new();
+        self.advance(); // Consume the opening '/'
+
+        while self.current != '/' {
+            if self.current == '\\' {
+                self.advance();
+                if self.current != '/' {
+                    pattern.push('\\');
+                }
+            }
+
+            if self.current == '\0' || self.current == '\n' {
+                return Err(LexerError::UnterminatedRegex(start_position));
+            }
+
+            pattern.push(self.current);
+            self.advance();
+        }
+
+        self.advance(); // Consume the closing '/'
+
+        let mut flags = String::new();
+        while self.current.is_alphabetic() {
+            if !"imsxU".contains(self.current) {
+                return Err(LexerError::UnexpectedCharacter(
+                    self.current,
+                    self.position.clone(),
+                ));
+            }
+            flags.push(self.current);
+            self.advance();
+        }
+
+        if !flags.is_empty() {
+            pattern = format!("(?{}){}", flags, pattern);
+        }
+
+        Ok(Token {
+            ttype: TokenType::Regex,
+            literal: pattern,
+            position: start_position,
+        })
+    }
+
     fn gen_number(&mut self) -> Token {
         let mut number = String::new();
 
@@ -313,6 +435,17 @@ impl Lexer {
             self.advance();
         }
 
+        // A fraction makes it a float, `1.foo` is still dot notation
+        if self.current == '.' && self.peek().is_ascii_digit() {
+            number.push(self.current);
+            self.advance();
+
+            while self.current.is_numeric() {
+                number.push(self.current);
+                self.advance();
+            }
+        }
+
         Token {
             ttype: TokenType::Number,
             literal: number,
@@ -7,6 +7,12 @@ pub struct Position {
     pub index: usize,
 }
 
+impl Default for Position {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Position {
     pub fn new() -> Self {
         Self {
@@ -43,7 +43,12 @@ pub enum TokenType {
     RBracket,
     Comma,
     Period,
+    Ellipsis,
+    Arrow,
+    QuestionDot,
+    Coalesce,
     String,
+    Regex,
     Eof,
 }
 
@@ -51,11 +56,13 @@ pub enum TokenType {
 pub enum KeywordType {
     Primitive(PrimitiveKind),
     Let,
+    Const,
     Return,
     True,
     False,
     If,
     Else,
+    Match,
     Fn,
     Do,
     End,
@@ -84,3 +91,16 @@ pub enum PrimitiveKind {
     Kv,
     Boolean,
 }
+
+/// Spelled as in a `let` type hint
+impl fmt::Display for PrimitiveKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrimitiveKind::String => write!(f, "str"),
+            PrimitiveKind::Number => write!(f, "number"),
+            PrimitiveKind::Array => write!(f, "arr"),
+            PrimitiveKind::Kv => write!(f, "kv"),
+            PrimitiveKind::Boolean => write!(f, "bool"),
+        }
+    }
+}
@@ -1,5 +1,6 @@
 pub mod ast;
 pub mod builtin;
+pub mod check;
 pub mod env;
 pub mod eval;
 pub mod lexer;
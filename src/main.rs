@@ -1,26 +1,95 @@
 use std::io::{stdout, Write};
 
-use crate::{ast::Program, eval::Evaluator};
-
-mod ast;
-mod builtin;
-mod env;
-mod eval;
-mod lexer;
-mod object;
-mod parser;
+use tammr::{ast::Program, check::Checker, eval::Evaluator, lexer, object, parser};
 
 fn main() {
-    let args: Vec<String> = std::env::args().collect();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    // `--strict` warns about `let` statements without a type hint and only
+    // accepts Booleans as conditions
+    let strict = args.iter().any(|arg| arg == "--strict");
+
+    // `tammr check file...` reports type errors without running anything
+    if args.first().is_some_and(|arg| arg == "check") {
+        let files: Vec<&String> = args[1..]
+            .iter()
+            .filter(|arg| !arg.starts_with("--"))
+            .collect();
+        if files.is_empty() {
+            eprintln!("usage: tammr check [--strict] FILE...");
+            std::process::exit(2);
+        }
+
+        let failed = files
+            .iter()
+            .filter(|file| !check_file(file, strict))
+            .count();
+        std::process::exit(if failed == 0 { 0 } else { 1 });
+    }
+    let file = args.iter().find(|arg| !arg.starts_with("--"));
 
-    if args.len() > 1 {
-        run_file(&args[1]);
+    if let Some(file) = file {
+        run_file(file, strict);
     } else {
-        repl();
+        repl(strict);
+    }
+}
+
+fn print_parse_errors(parser: &parser::Parser) {
+    for error in &parser.errors {
+        println!("Error: {}", error);
     }
 }
 
-fn run_file(file: &String) {
+fn print_warnings(evaluator: &mut Evaluator) {
+    for warning in evaluator.take_warnings() {
+        eprintln!("Warning: {}", warning);
+    }
+}
+
+/// Prints the diagnostics for one file, returns whether there were none.
+fn check_file(file: &String, strict: bool) -> bool {
+    let input = match std::fs::read_to_string(file) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("{}: cannot read file: {}", file, e);
+            return false;
+        }
+    };
+
+    let mut l = lexer::Lexer::new(input);
+    let tokens = match l.gen_tokens() {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            eprintln!("{}: {}", file, e);
+            return false;
+        }
+    };
+
+    let mut parser = parser::Parser::new(tokens);
+    let Some(program) = parser.parse_program() else {
+        for error in &parser.errors {
+            eprintln!("{}: {}", file, error);
+        }
+        return false;
+    };
+
+    let mut checker = Checker::new();
+    checker.set_strict(strict);
+    let diagnostics = checker.check(&program);
+    for diagnostic in &diagnostics {
+        println!(
+            "{}:{}: {}",
+            file,
+            diagnostic.position.line + 1,
+            diagnostic.message
+        );
+    }
+
+    diagnostics.is_empty()
+}
+
+fn run_file(file: &String, strict: bool) {
     let input = std::fs::read_to_string(file).expect("Something went wrong when opning the file");
 
     let mut l = lexer::Lexer::new(input);
@@ -31,18 +100,28 @@ fn run_file(file: &String) {
 
     if let Some(program) = program {
         let mut evaluator = Evaluator::new();
+        evaluator.set_strict(strict);
+
+        let result = evaluator.eval(&program);
+        print_warnings(&mut evaluator);
 
-        if let Some(result) = evaluator.eval(&program) {
+        if let Some(result) = result {
             match result {
                 object::Object::Null => (),
                 object::Object::Error(msg) => println!("Error: {}", msg),
                 _ => (),
             };
         }
+    } else {
+        print_parse_errors(&parser);
     }
 }
 
-fn repl() {
+fn repl(strict: bool) {
+    // Kept across lines so later input can use earlier definitions
+    let mut evaluator = Evaluator::new();
+    evaluator.set_strict(strict);
+
     loop {
         print!(">> ");
         let mut input = String::new();
@@ -53,6 +132,12 @@ fn repl() {
             break;
         }
 
+        // `:type expr` shows the type of a value, with the signature of functions
+        let show_type = input.starts_with(":type ");
+        if show_type {
+            input = input[":type ".len()..].to_string();
+        }
+
         let mut l = lexer::Lexer::new(input);
         let tokens = l.gen_tokens();
 
@@ -70,15 +155,19 @@ fn repl() {
             //     println!("AST {:?}", stmt);
             // }
 
-            let mut evaluator = Evaluator::new();
+            let result = evaluator.eval(&program);
+            print_warnings(&mut evaluator);
 
-            if let Some(result) = evaluator.eval(&program) {
+            if let Some(result) = result {
                 match result {
-                    object::Object::Null => println!("null"),
                     object::Object::Error(msg) => println!("Error: {}", msg),
+                    result if show_type => println!("{}", result.type_signature()),
+                    object::Object::Null => println!("null"),
                     _ => (),
                 };
             }
+        } else {
+            print_parse_errors(&parser);
         }
     }
 }
@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+
+use super::Object;
+
+/// The subset of objects that can be used as hash keys.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum HashKey {
+    Integer(i64),
+    Boolean(bool),
+    String(String),
+}
+
+impl HashKey {
+    pub fn from_object(obj: &Object) -> Option<HashKey> {
+        match obj {
+            Object::Integer(int) => Some(HashKey::Integer(*int)),
+            Object::Boolean(bool) => Some(HashKey::Boolean(*bool)),
+            Object::String(string) => Some(HashKey::String(string.clone())),
+            _ => None,
+        }
+    }
+
+    pub fn to_object(&self) -> Object {
+        match self {
+            HashKey::Integer(int) => Object::Integer(*int),
+            HashKey::Boolean(bool) => Object::Boolean(*bool),
+            HashKey::String(string) => Object::String(string.clone()),
+        }
+    }
+}
+
+impl From<&str> for HashKey {
+    fn from(key: &str) -> Self {
+        HashKey::String(key.to_string())
+    }
+}
+
+impl From<String> for HashKey {
+    fn from(key: String) -> Self {
+        HashKey::String(key)
+    }
+}
+
+impl std::fmt::Display for HashKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            HashKey::Integer(int) => write!(f, "{}", int),
+            HashKey::Boolean(bool) => write!(f, "{}", bool),
+            HashKey::String(string) => write!(f, "{}", string),
+        }
+    }
+}
+
+/// Insertion-ordered map behind `Object::Hash`. Inserting an existing key
+/// replaces its value but keeps its original position.
+#[derive(Debug, Clone, Default)]
+pub struct OrderedHash {
+    entries: Vec<(HashKey, Object)>,
+    index: HashMap<HashKey, usize>,
+}
+
+impl OrderedHash {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn insert(&mut self, key: HashKey, value: Object) -> Option<Object> {
+        match self.index.get(&key) {
+            Some(&i) => Some(std::mem::replace(&mut self.entries[i].1, value)),
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    pub fn get(&self, key: &HashKey) -> Option<&Object> {
+        self.index.get(key).map(|&i| &self.entries[i].1)
+    }
+
+    pub fn get_mut(&mut self, key: &HashKey) -> Option<&mut Object> {
+        self.index.get(key).map(|&i| &mut self.entries[i].1)
+    }
+
+    pub fn contains_key(&self, key: &HashKey) -> bool {
+        self.index.contains_key(key)
+    }
+
+    pub fn remove(&mut self, key: &HashKey) -> Option<Object> {
+        let i = self.index.remove(key)?;
+        let (_, value) = self.entries.remove(i);
+
+        for (_, position) in self.index.iter_mut() {
+            if *position > i {
+                *position -= 1;
+            }
+        }
+
+        Some(value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&HashKey, &Object)> {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&HashKey, &mut Object)> {
+        self.entries.iter_mut().map(|(key, value)| (&*key, value))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &HashKey> {
+        self.entries.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Object> {
+        self.entries.iter().map(|(_, value)| value)
+    }
+}
+
+impl PartialEq for OrderedHash {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl FromIterator<(HashKey, Object)> for OrderedHash {
+    fn from_iter<I: IntoIterator<Item = (HashKey, Object)>>(iter: I) -> Self {
+        let mut hash = OrderedHash::new();
+        for (key, value) in iter {
+            hash.insert(key, value);
+        }
+        hash
+    }
+}
+
+impl IntoIterator for OrderedHash {
+    type Item = (HashKey, Object);
+    type IntoIter = std::vec::IntoIter<(HashKey, Object)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a OrderedHash {
+    type Item = (&'a HashKey, &'a Object);
+    type IntoIter = std::iter::Map<
+        std::slice::Iter<'a, (HashKey, Object)>,
+        fn(&'a (HashKey, Object)) -> (&'a HashKey, &'a Object),
+    >;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+}
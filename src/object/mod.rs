@@ -1,3 +1,6 @@
+mod hash;
+#[allow(clippy::module_inception)]
 mod object;
 
-pub use object::Object;
+pub use hash::{HashKey, OrderedHash};
+pub use object::{Object, Visited};
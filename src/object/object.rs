@@ -1,32 +1,366 @@
 use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::rc::Rc;
 
-use crate::ast::{BlockStatement, Identifier};
+use regex::Regex;
+
+use super::OrderedHash;
+use crate::ast::{signature, BlockStatement, Parameter};
+use crate::builtin::{Builtin, ProcessHandle};
 use crate::env::Env;
+use crate::lexer::PrimitiveKind;
 
-#[derive(PartialEq, Debug, Clone, Eq)]
+#[derive(Debug, Clone)]
 pub enum Object {
     Integer(i64),
+    Float(f64),
     Boolean(bool),
     Null,
     Return(Box<Object>),
     Error(String),
     Empty,
     Function {
-        parameters: Vec<Identifier>,
+        parameters: Vec<Parameter>,
+        return_kind: Option<PrimitiveKind>,
         body: BlockStatement,
         env: Rc<RefCell<Env>>,
     },
     String(String),
-    BuiltinFunction(fn(Vec<Object>) -> Object),
-    Array(Vec<Object>),
-    Hash(Vec<(Object, Object)>),
+    BuiltinFunction(Builtin),
+    /// Arrays and hashes are shared, assigning or passing one hands out
+    /// the same storage. `copy` and `deepCopy` make independent values.
+    Array(Rc<RefCell<Vec<Object>>>),
+    Hash(Rc<RefCell<OrderedHash>>),
+    Process(Rc<RefCell<ProcessHandle>>),
+    Regex(Rc<Regex>),
+}
+
+/// The arrays and hashes a recursive walk is currently inside. Since they are
+/// shared, a collection can contain itself, and following it again would
+/// recurse until the stack overflows.
+#[derive(Default)]
+pub struct Visited(HashSet<*const ()>);
+
+impl Visited {
+    /// Marks the array or hash as entered. Returns false if the walk is
+    /// already inside it.
+    pub fn enter<T>(&mut self, rc: &Rc<T>) -> bool {
+        self.0.insert(Rc::as_ptr(rc) as *const ())
+    }
+
+    pub fn leave<T>(&mut self, rc: &Rc<T>) {
+        self.0.remove(&(Rc::as_ptr(rc) as *const ()));
+    }
+}
+
+/// Compares like `<` does on floats, so `-0.0` and `0.0` are equal. NaN has
+/// no place in the order.
+fn float_cmp(a: f64, b: f64) -> Result<Ordering, String> {
+    a.partial_cmp(&b)
+        .ok_or_else(|| "Cannot compare NaN".to_string())
+}
+
+fn contains_itself(action: &str) -> String {
+    format!("Cannot {} a value that contains itself", action)
+}
+
+impl Object {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Object::Integer(_) => "Integer",
+            Object::Float(_) => "Float",
+            Object::Boolean(_) => "Boolean",
+            Object::Null => "Null",
+            Object::Return(_) => "Return",
+            Object::Error(_) => "Error",
+            Object::Empty => "Empty",
+            Object::Function { .. } => "Function",
+            Object::String(_) => "String",
+            Object::BuiltinFunction(_) => "BuiltinFunction",
+            Object::Array(_) => "Array",
+            Object::Hash(_) => "Hash",
+            Object::Process(_) => "Process",
+            Object::Regex(_) => "Regex",
+        }
+    }
+
+    /// The type of the value spelled like the type hints, what `type(x)`
+    /// returns.
+    pub fn hint_name(&self) -> &'static str {
+        match self {
+            Object::String(_) => "str",
+            Object::Integer(_) | Object::Float(_) => "number",
+            Object::Array(_) => "arr",
+            Object::Hash(_) => "kv",
+            Object::Boolean(_) => "bool",
+            Object::Null => "null",
+            Object::Function { .. } | Object::BuiltinFunction(_) => "function",
+            Object::Process(_) => "process",
+            Object::Regex(_) => "regex",
+            Object::Return(_) => "return",
+            Object::Error(_) => "error",
+            Object::Empty => "empty",
+        }
+    }
+
+    /// Whether the value fits a type hint, also what `x is kind` checks.
+    pub fn fits(&self, kind: &PrimitiveKind) -> bool {
+        matches!(
+            (kind, self),
+            (PrimitiveKind::String, Object::String(_))
+                | (PrimitiveKind::Number, Object::Integer(_) | Object::Float(_))
+                | (PrimitiveKind::Array, Object::Array(_))
+                | (PrimitiveKind::Kv, Object::Hash(_))
+                | (PrimitiveKind::Boolean, Object::Boolean(_))
+        )
+    }
+
+    /// The type as spelled in hints, with the signature for Tammr functions:
+    /// `function(str name, number n) -> str`
+    pub fn type_signature(&self) -> String {
+        match self {
+            Object::Function {
+                parameters,
+                return_kind,
+                ..
+            } => format!("function{}", signature(parameters, return_kind)),
+            other => other.hint_name().to_string(),
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        matches!(self, Object::Error(_))
+    }
+
+    /// Equality as `==` sees it: numbers by value whether integer or float,
+    /// arrays and hashes by their contents, and functions only when they are
+    /// the same function. Values of different types are never equal.
+    pub fn equals(&self, other: &Object) -> Result<bool, String> {
+        self.equals_within(other, &mut Visited::default())
+    }
+
+    fn equals_within(&self, other: &Object, visited: &mut Visited) -> Result<bool, String> {
+        let equal = match (self, other) {
+            (Object::Integer(int), Object::Float(float))
+            | (Object::Float(float), Object::Integer(int)) => *int as f64 == *float,
+            (Object::Array(a), Object::Array(b)) => {
+                if Rc::ptr_eq(a, b) {
+                    return Ok(true);
+                }
+                if !visited.enter(a) || !visited.enter(b) {
+                    return Err(contains_itself("compare"));
+                }
+
+                let (items, others) = (a.borrow(), b.borrow());
+                let mut equal = items.len() == others.len();
+                for (item, other) in items.iter().zip(others.iter()) {
+                    if !equal {
+                        break;
+                    }
+                    equal = item.equals_within(other, visited)?;
+                }
+
+                visited.leave(a);
+                visited.leave(b);
+                equal
+            }
+            (Object::Hash(a), Object::Hash(b)) => {
+                if Rc::ptr_eq(a, b) {
+                    return Ok(true);
+                }
+                if !visited.enter(a) || !visited.enter(b) {
+                    return Err(contains_itself("compare"));
+                }
+
+                let (pairs, others) = (a.borrow(), b.borrow());
+                let mut equal = pairs.len() == others.len();
+                for (key, value) in pairs.iter() {
+                    if !equal {
+                        break;
+                    }
+                    equal = match others.get(key) {
+                        Some(other) => value.equals_within(other, visited)?,
+                        None => false,
+                    };
+                }
+
+                visited.leave(a);
+                visited.leave(b);
+                equal
+            }
+            (a, b) => a == b,
+        };
+
+        Ok(equal)
+    }
+
+    /// The order `<`, `>` and `sort` use: numbers by value, strings and
+    /// booleans (false first) as in Rust, and arrays item by item with a
+    /// shorter prefix first. Other pairs cannot be ordered.
+    pub fn compare(&self, other: &Object) -> Result<Ordering, String> {
+        self.compare_within(other, &mut Visited::default())
+    }
+
+    fn compare_within(&self, other: &Object, visited: &mut Visited) -> Result<Ordering, String> {
+        let ordering = match (self, other) {
+            (Object::Integer(a), Object::Integer(b)) => a.cmp(b),
+            (Object::Float(a), Object::Float(b)) => float_cmp(*a, *b)?,
+            (Object::Integer(a), Object::Float(b)) => float_cmp(*a as f64, *b)?,
+            (Object::Float(a), Object::Integer(b)) => float_cmp(*a, *b as f64)?,
+            (Object::String(a), Object::String(b)) => a.cmp(b),
+            (Object::Boolean(a), Object::Boolean(b)) => a.cmp(b),
+            (Object::Array(a), Object::Array(b)) => {
+                if Rc::ptr_eq(a, b) {
+                    return Ok(Ordering::Equal);
+                }
+                if !visited.enter(a) || !visited.enter(b) {
+                    return Err(contains_itself("compare"));
+                }
+
+                let (items, others) = (a.borrow(), b.borrow());
+                let mut ordering = Ordering::Equal;
+                for (item, other) in items.iter().zip(others.iter()) {
+                    ordering = item.compare_within(other, visited)?;
+                    if ordering != Ordering::Equal {
+                        break;
+                    }
+                }
+
+                visited.leave(a);
+                visited.leave(b);
+                ordering.then(items.len().cmp(&others.len()))
+            }
+            _ => {
+                return Err(format!(
+                    "Cannot compare {} and {}",
+                    self.type_name(),
+                    other.type_name()
+                ))
+            }
+        };
+
+        Ok(ordering)
+    }
+
+    /// How the value counts as a condition: null, false, zero and empty
+    /// strings, arrays and hashes are false, everything else is true.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Object::Null | Object::Empty => false,
+            Object::Boolean(bool) => *bool,
+            Object::Integer(int) => *int != 0,
+            Object::Float(float) => *float != 0.0,
+            Object::String(string) => !string.is_empty(),
+            Object::Array(array) => !array.borrow().is_empty(),
+            Object::Hash(hash) => !hash.borrow().is_empty(),
+            _ => true,
+        }
+    }
+
+    pub fn array(items: Vec<Object>) -> Object {
+        Object::Array(Rc::new(RefCell::new(items)))
+    }
+
+    pub fn hash(hash: OrderedHash) -> Object {
+        Object::Hash(Rc::new(RefCell::new(hash)))
+    }
+
+    /// A new array or hash holding the same elements, other values as they are.
+    pub fn shallow_copy(&self) -> Object {
+        match self {
+            Object::Array(items) => Object::array(items.borrow().clone()),
+            Object::Hash(hash) => Object::hash(hash.borrow().clone()),
+            _ => self.clone(),
+        }
+    }
+
+    /// Copies nested arrays and hashes too, so nothing is shared with `self`.
+    pub fn deep_copy(&self) -> Object {
+        self.copy_within(&mut Visited::default())
+            .unwrap_or_else(Object::Error)
+    }
+
+    fn copy_within(&self, visited: &mut Visited) -> Result<Object, String> {
+        let copy = match self {
+            Object::Array(items) => {
+                if !visited.enter(items) {
+                    return Err(contains_itself("copy"));
+                }
+
+                let mut copies = Vec::new();
+                for item in items.borrow().iter() {
+                    copies.push(item.copy_within(visited)?);
+                }
+
+                visited.leave(items);
+                Object::array(copies)
+            }
+            Object::Hash(hash) => {
+                if !visited.enter(hash) {
+                    return Err(contains_itself("copy"));
+                }
+
+                let mut copies = OrderedHash::new();
+                for (key, value) in hash.borrow().iter() {
+                    copies.insert(key.clone(), value.copy_within(visited)?);
+                }
+
+                visited.leave(hash);
+                Object::hash(copies)
+            }
+            _ => self.clone(),
+        };
+
+        Ok(copy)
+    }
+}
+
+impl PartialEq for Object {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Object::Integer(a), Object::Integer(b)) => a == b,
+            (Object::Float(a), Object::Float(b)) => a == b,
+            (Object::Boolean(a), Object::Boolean(b)) => a == b,
+            (Object::Null, Object::Null) => true,
+            (Object::Return(a), Object::Return(b)) => a == b,
+            (Object::Error(a), Object::Error(b)) => a == b,
+            (Object::Empty, Object::Empty) => true,
+            (
+                Object::Function {
+                    parameters: a_params,
+                    return_kind: a_return,
+                    body: a_body,
+                    env: a_env,
+                },
+                Object::Function {
+                    parameters: b_params,
+                    return_kind: b_return,
+                    body: b_body,
+                    env: b_env,
+                },
+            ) => {
+                a_params == b_params
+                    && a_return == b_return
+                    && a_body == b_body
+                    && Rc::ptr_eq(a_env, b_env)
+            }
+            (Object::String(a), Object::String(b)) => a == b,
+            (Object::BuiltinFunction(a), Object::BuiltinFunction(b)) => a == b,
+            (Object::Array(a), Object::Array(b)) => Rc::ptr_eq(a, b) || a == b,
+            (Object::Hash(a), Object::Hash(b)) => Rc::ptr_eq(a, b) || a == b,
+            (Object::Process(a), Object::Process(b)) => Rc::ptr_eq(a, b),
+            (Object::Regex(a), Object::Regex(b)) => a.as_str() == b.as_str(),
+            _ => false,
+        }
+    }
 }
 
 impl std::fmt::Display for Object {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             Object::Integer(int) => write!(f, "{}", int),
+            Object::Float(float) => write!(f, "{:?}", float),
             Object::Boolean(bool) => write!(f, "{}", bool),
             Object::Null => write!(f, "null"),
             Object::Return(obj) => write!(f, "{}", obj),
@@ -34,20 +368,28 @@ impl std::fmt::Display for Object {
             Object::Empty => write!(f, ""),
             Object::Function {
                 parameters,
+                return_kind,
                 body,
                 env: _,
             } => {
-                let mut params = String::new();
-                for param in parameters {
-                    params.push_str(&format!("{}, ", param));
-                }
-                write!(f, "fn({}) {{\n{:?}\n}}", params, body)
+                write!(
+                    f,
+                    "fn{} {{\n{:?}\n}}",
+                    signature(parameters, return_kind),
+                    body
+                )
             }
             Object::String(string) => {
                 write!(f, "{}", string)
             }
             Object::BuiltinFunction(_) => write!(f, "builtin function"),
             Object::Array(array) => {
+                // Held mutably while printing, so an array containing itself
+                // finds the borrow taken and prints `[...]` instead of recursing
+                let array = match array.try_borrow_mut() {
+                    Ok(array) => array,
+                    Err(_) => return write!(f, "[...]"),
+                };
                 write!(f, "[")?;
                 for (i, obj) in array.iter().enumerate() {
                     if i == array.len() - 1 {
@@ -59,6 +401,10 @@ impl std::fmt::Display for Object {
                 write!(f, "]")
             }
             Object::Hash(hash) => {
+                let hash = match hash.try_borrow_mut() {
+                    Ok(hash) => hash,
+                    Err(_) => return write!(f, "{{...}}"),
+                };
                 write!(f, "{{")?;
                 for (i, (key, value)) in hash.iter().enumerate() {
                     if i == hash.len() - 1 {
@@ -69,6 +415,8 @@ impl std::fmt::Display for Object {
                 }
                 write!(f, "}}")
             }
+            Object::Process(handle) => write!(f, "{:?}", handle.borrow()),
+            Object::Regex(regex) => write!(f, "/{}/", regex.as_str()),
         }
     }
 }
@@ -1,28 +1,51 @@
 // parser/expression.rs
 use super::precedence::Precedence;
 use super::Parser;
-use crate::ast::{Expression, Identifier, Literal};
+use crate::ast::{Expression, Identifier, Literal, Parameter, TypeTest};
 use crate::lexer::{KeywordType, TokenType};
 
 impl Parser {
     pub(crate) fn parse_expression(&mut self, precedence: Precedence) -> Option<Expression> {
         // Prefix
-        let mut left = match self.current_token.ttype {
+        let left = match self.current_token.ttype {
             TokenType::Ident => self.parse_identifier(),
+            // The conversion builtins `str(x)` and `bool(x)` share their
+            // names with type hints
+            TokenType::Keyword(KeywordType::Primitive(_))
+                if self.peek_token.ttype == TokenType::LParen =>
+            {
+                self.parse_identifier()
+            }
             TokenType::String => self.parse_string_literal(),
+            TokenType::Regex => Some(Expression::Literal(Literal::Regex(
+                self.current_token.literal.clone(),
+            ))),
             TokenType::Number => self.parse_integer_literal(),
-            TokenType::Bang | TokenType::Sub => self.parse_prefix_expression(),
+            TokenType::Bang | TokenType::Sub | TokenType::Keyword(KeywordType::Not) => {
+                self.parse_prefix_expression()
+            }
             TokenType::Keyword(KeywordType::True) | TokenType::Keyword(KeywordType::False) => {
                 self.parse_boolean()
             }
+            TokenType::Keyword(KeywordType::Null) => Some(Expression::Literal(Literal::Null)),
             TokenType::LBrace => self.parse_hash_expr(),
             TokenType::LParen => self.parse_group_expr(),
             TokenType::LBracket => self.parse_array_literal(),
             TokenType::Keyword(KeywordType::If) => self.parse_if_expr(),
             TokenType::Keyword(KeywordType::Fn) => self.parse_fn_literal(),
+            TokenType::Keyword(KeywordType::Match) => self.parse_match_expr(),
             _ => return None,
         };
 
+        self.parse_infix_operators(left, precedence)
+    }
+
+    /// Continues a Pratt parse from an already parsed `left` operand.
+    pub(crate) fn parse_infix_operators(
+        &mut self,
+        mut left: Option<Expression>,
+        precedence: Precedence,
+    ) -> Option<Expression> {
         // Infix
         while self.peek_token.ttype != TokenType::Semicolon && precedence < self.peek_precedence() {
             self.next_token();
@@ -36,10 +59,14 @@ impl Parser {
                 | TokenType::Mul
                 | TokenType::NotEq
                 | TokenType::Eq
-                | TokenType::Sub => self.parse_infix_expression(left.unwrap()),
-                TokenType::LParen => self.parse_fn_call(left.unwrap()),
-                TokenType::LBracket => self.parse_index_expression(left.unwrap()),
-                TokenType::Period => self.parse_dot_notation(left.unwrap()),
+                | TokenType::Coalesce
+                | TokenType::Keyword(KeywordType::And)
+                | TokenType::Keyword(KeywordType::Or)
+                | TokenType::Sub => self.parse_infix_expression(left?),
+                TokenType::LParen => self.parse_fn_call(left?),
+                TokenType::LBracket => self.parse_index_expression(left?),
+                TokenType::Period | TokenType::QuestionDot => self.parse_dot_notation(left?),
+                TokenType::Keyword(KeywordType::Is) => self.parse_is_expression(left?),
                 _ => return left,
             };
         }
@@ -48,26 +75,55 @@ impl Parser {
     }
 
     fn parse_dot_notation(&mut self, left: Expression) -> Option<Expression> {
+        let optional = self.current_token.ttype == TokenType::QuestionDot;
         self.next_token();
 
-        let right = self.parse_expression(Precedence::Dot);
+        let right = match self.current_token.ttype {
+            // Members may be named like keywords, `re.match(text)`
+            TokenType::Keyword(_) => Some(Expression::Identifier(Identifier {
+                token: self.current_token.clone(),
+                value: self.current_token.literal.clone(),
+            })),
+            _ => self.parse_expression(Precedence::Dot),
+        };
 
         if let Some(right) = right {
             Some(Expression::DotNotation {
                 token: self.current_token.clone(),
                 left: Box::new(left),
                 right: Box::new(right),
+                optional,
             })
         } else {
             None
         }
     }
 
+    fn parse_is_expression(&mut self, value: Expression) -> Option<Expression> {
+        let token = self.current_token.clone();
+
+        let test = match self.parse_primitive_kind() {
+            Some(kind) => TypeTest::Kind(kind),
+            None if self.expect_peek(TokenType::Keyword(KeywordType::Null)) => TypeTest::Null,
+            None => {
+                let found = self.peek_token.literal.clone();
+                self.error(&format!("expected a type after `is`, got `{}`", found));
+                return None;
+            }
+        };
+
+        Some(Expression::Is {
+            token,
+            value: Box::new(value),
+            test,
+        })
+    }
+
     fn parse_fn_call(&mut self, function: Expression) -> Option<Expression> {
         Some(Expression::FunctionCall {
             token: self.current_token.clone(),
             function: Box::new(function),
-            arguments: self.parse_fn_arguments(),
+            arguments: self.parse_fn_arguments()?,
         })
     }
 
@@ -77,14 +133,14 @@ impl Parser {
         while self.peek_token.ttype != TokenType::RBrace {
             self.next_token();
 
-            let key = self.parse_expression(Precedence::Lowest).unwrap();
+            let key = self.parse_expression(Precedence::Lowest)?;
 
             if !self.expect_peek(TokenType::Colon) {
                 return None;
             }
 
             self.next_token();
-            let value = self.parse_expression(Precedence::Lowest).unwrap();
+            let value = self.parse_expression(Precedence::Lowest)?;
 
             pairs.push((key, value));
 
@@ -100,52 +156,87 @@ impl Parser {
         Some(Expression::Literal(Literal::Hash(pairs)))
     }
 
+    /// `left[index]`, or a slice `left[start:end:step]` where every part is
+    /// optional.
     fn parse_index_expression(&mut self, left: Expression) -> Option<Expression> {
+        let token = self.current_token.clone();
+        let start = self.parse_slice_bound();
+
+        if self.peek_token.ttype != TokenType::Colon {
+            if !self.expect_peek(TokenType::RBracket) {
+                return None;
+            }
+
+            return Some(Expression::IndexExpression {
+                token: self.current_token.clone(),
+                left: Box::new(left),
+                index: start?,
+            });
+        }
+
         self.next_token();
+        let end = self.parse_slice_bound();
 
-        let index = self.parse_expression(Precedence::Lowest);
+        let step = if self.peek_token.ttype == TokenType::Colon {
+            self.next_token();
+            self.parse_slice_bound()
+        } else {
+            None
+        };
 
         if !self.expect_peek(TokenType::RBracket) {
             return None;
         }
 
-        Some(Expression::IndexExpression {
-            token: self.current_token.clone(),
+        Some(Expression::SliceExpression {
+            token,
             left: Box::new(left),
-            index: Box::new(index.unwrap()),
+            start,
+            end,
+            step,
         })
     }
 
+    fn parse_slice_bound(&mut self) -> Option<Box<Expression>> {
+        match self.peek_token.ttype {
+            TokenType::Colon | TokenType::RBracket => None,
+            _ => {
+                self.next_token();
+                self.parse_expression(Precedence::Lowest).map(Box::new)
+            }
+        }
+    }
+
     fn parse_array_literal(&mut self) -> Option<Expression> {
         Some(Expression::Literal(Literal::Array(
-            self.parse_array_elements(),
+            self.parse_array_elements()?,
         )))
     }
 
-    fn parse_array_elements(&mut self) -> Vec<Expression> {
+    fn parse_array_elements(&mut self) -> Option<Vec<Expression>> {
         let mut elements = Vec::new();
 
         if self.peek_token.ttype == TokenType::RBracket {
             self.next_token();
-            return elements;
+            return Some(elements);
         }
 
         self.next_token();
 
-        elements.push(self.parse_expression(Precedence::Lowest).unwrap());
+        elements.push(self.parse_expression(Precedence::Lowest)?);
 
         while self.peek_token.ttype == TokenType::Comma {
             self.next_token();
             self.next_token();
 
-            elements.push(self.parse_expression(Precedence::Lowest).unwrap());
+            elements.push(self.parse_expression(Precedence::Lowest)?);
         }
 
         if !self.expect_peek(TokenType::RBracket) {
-            return Vec::new();
+            return None;
         }
 
-        elements
+        Some(elements)
     }
 
     fn parse_string_literal(&mut self) -> Option<Expression> {
@@ -154,30 +245,30 @@ impl Parser {
         )))
     }
 
-    fn parse_fn_arguments(&mut self) -> Vec<Expression> {
+    fn parse_fn_arguments(&mut self) -> Option<Vec<Expression>> {
         let mut args = Vec::new();
 
         if self.peek_token.ttype == TokenType::RParen {
             self.next_token();
-            return args;
+            return Some(args);
         }
 
         self.next_token();
 
-        args.push(self.parse_expression(Precedence::Lowest).unwrap());
+        args.push(self.parse_expression(Precedence::Lowest)?);
 
         while self.peek_token.ttype == TokenType::Comma {
             self.next_token();
             self.next_token();
 
-            args.push(self.parse_expression(Precedence::Lowest).unwrap());
+            args.push(self.parse_expression(Precedence::Lowest)?);
         }
 
         if !self.expect_peek(TokenType::RParen) {
-            return Vec::new();
+            return None;
         }
 
-        args
+        Some(args)
     }
 
     fn parse_fn_literal(&mut self) -> Option<Expression> {
@@ -189,6 +280,12 @@ impl Parser {
 
         let parameters = self.parse_fn_parameters();
 
+        let mut return_kind = None;
+        if self.peek_token.ttype == TokenType::Arrow {
+            self.next_token();
+            return_kind = Some(self.parse_primitive_kind()?);
+        }
+
         if !self.expect_peek(TokenType::LBrace) {
             return None;
         }
@@ -198,44 +295,44 @@ impl Parser {
         Some(Expression::FunctionLiteral {
             token,
             parameters,
+            return_kind,
             body: Box::new(body),
         })
     }
 
-    fn parse_fn_parameters(&mut self) -> Vec<Identifier> {
-        let mut identifiers = Vec::new();
+    fn parse_fn_parameters(&mut self) -> Vec<Parameter> {
+        let mut parameters = Vec::new();
 
         if self.peek_token.ttype == TokenType::RParen {
             self.next_token();
-            return identifiers;
+            return parameters;
         }
 
-        self.next_token();
-
-        let ident = Identifier {
-            token: self.current_token.clone(),
-            value: self.current_token.literal.clone(),
-        };
-
-        identifiers.push(ident);
+        parameters.push(self.parse_fn_parameter());
 
         while self.peek_token.ttype == TokenType::Comma {
             self.next_token();
-            self.next_token();
-
-            let ident = Identifier {
-                token: self.current_token.clone(),
-                value: self.current_token.literal.clone(),
-            };
-
-            identifiers.push(ident);
+            parameters.push(self.parse_fn_parameter());
         }
 
         if !self.expect_peek(TokenType::RParen) {
             return Vec::new();
         }
 
-        identifiers
+        parameters
+    }
+
+    /// A name, optionally preceded by its type hint.
+    fn parse_fn_parameter(&mut self) -> Parameter {
+        let kind = self.parse_primitive_kind();
+        self.next_token();
+
+        let name = Identifier {
+            token: self.current_token.clone(),
+            value: self.current_token.literal.clone(),
+        };
+
+        Parameter { name, kind }
     }
 
     fn parse_if_expr(&mut self) -> Option<Expression> {
@@ -264,7 +361,7 @@ impl Parser {
 
         Some(Expression::If {
             token,
-            condition: Box::new(condition.unwrap()),
+            condition: Box::new(condition?),
             consequence: Box::new(consequence),
             alternative: alternative.map(Box::new),
         })
@@ -302,12 +399,18 @@ impl Parser {
         }
     }
 
+    /// `!x` and `-x` bind tightly, `not` takes everything up to the next
+    /// `and` or `or`, so `not a == b` is `not (a == b)`.
     fn parse_prefix_expression(&mut self) -> Option<Expression> {
         let operator = self.current_token.literal.clone();
+        let precedence = match self.current_token.ttype {
+            TokenType::Keyword(KeywordType::Not) => Precedence::And,
+            _ => Precedence::Prefix,
+        };
 
         self.next_token();
 
-        let right = self.parse_expression(Precedence::Prefix);
+        let right = self.parse_expression(precedence);
 
         if let Some(right) = right {
             Some(Expression::Prefix {
@@ -326,7 +429,12 @@ impl Parser {
         )))
     }
 
-    fn parse_integer_literal(&mut self) -> Option<Expression> {
+    pub(super) fn parse_integer_literal(&mut self) -> Option<Expression> {
+        if self.current_token.literal.contains('.') {
+            let float = self.current_token.literal.parse::<f64>().ok()?;
+            return Some(Expression::Literal(Literal::Float(float)));
+        }
+
         let int = self.current_token.literal.parse::<i64>().unwrap();
         let lit = Expression::Literal(Literal::Integer(int));
 
@@ -1,5 +1,7 @@
 mod expression;
+#[allow(clippy::module_inception)]
 mod parser;
+mod pattern;
 mod precedence;
 mod statement;
 
@@ -1,12 +1,15 @@
 use super::precedence::Precedence;
 use crate::ast::Program;
-use crate::lexer::{Token, TokenType};
+use crate::lexer::{KeywordType, PrimitiveKind, Token, TokenType};
 
 pub struct Parser {
     pub current_token: Token,
     pub peek_token: Token,
     pub tokens: Vec<Token>,
     pub index: usize,
+    /// Mistakes that leave no sensible program, `parse_program` returns
+    /// `None` if there are any
+    pub errors: Vec<String>,
 }
 
 impl Parser {
@@ -16,6 +19,7 @@ impl Parser {
             peek_token: tokens[1].clone(),
             tokens,
             index: 0,
+            errors: Vec::new(),
         }
     }
 
@@ -31,7 +35,20 @@ impl Parser {
             self.next_token();
         }
 
-        Some(program)
+        if self.errors.is_empty() {
+            Some(program)
+        } else {
+            None
+        }
+    }
+
+    /// Records a parse error at the line of the current token.
+    pub(crate) fn error(&mut self, msg: &str) {
+        self.errors.push(format!(
+            "Parse error at line {}: {}",
+            self.current_token.position.line + 1,
+            msg
+        ));
     }
 
     pub(crate) fn expect_peek(&mut self, ttype: TokenType) -> bool {
@@ -43,6 +60,17 @@ impl Parser {
         }
     }
 
+    /// Consumes a type hint keyword if one comes next.
+    pub(crate) fn parse_primitive_kind(&mut self) -> Option<PrimitiveKind> {
+        let kind = match &self.peek_token.ttype {
+            TokenType::Keyword(KeywordType::Primitive(p)) => p.clone(),
+            _ => return None,
+        };
+
+        self.next_token();
+        Some(kind)
+    }
+
     pub(crate) fn next_token(&mut self) {
         self.index += 1;
         self.current_token = self.tokens[self.index].clone();
@@ -53,13 +81,19 @@ impl Parser {
 
     pub(crate) fn token_precedence(&mut self, ttype: TokenType) -> Precedence {
         match ttype {
-            TokenType::Assign | TokenType::NotEq | TokenType::Eq => Precedence::Equals,
+            TokenType::Keyword(KeywordType::Or) => Precedence::Or,
+            TokenType::Keyword(KeywordType::And) => Precedence::And,
+            TokenType::Coalesce => Precedence::Coalesce,
+            TokenType::Assign
+            | TokenType::NotEq
+            | TokenType::Eq
+            | TokenType::Keyword(KeywordType::Is) => Precedence::Equals,
             TokenType::Lt | TokenType::Gt => Precedence::LessGreater,
             TokenType::Add | TokenType::Sub => Precedence::Sum,
             TokenType::Div | TokenType::Mul => Precedence::Product,
             TokenType::LParen => Precedence::Call,
             TokenType::LBracket => Precedence::Index,
-            TokenType::Period => Precedence::Dot,
+            TokenType::Period | TokenType::QuestionDot => Precedence::Dot,
             _ => Precedence::Lowest,
         }
     }
@@ -0,0 +1,214 @@
+use super::precedence::Precedence;
+use super::Parser;
+use crate::ast::{Expression, Identifier, Literal, MatchArm, Pattern};
+use crate::lexer::{KeywordType, TokenType};
+
+impl Parser {
+    /// `match value do pattern -> result ... end`. Arms may be separated by
+    /// commas, which is needed when the next pattern starts with `[` or `-`.
+    pub(super) fn parse_match_expr(&mut self) -> Option<Expression> {
+        let token = self.current_token.clone();
+
+        self.next_token();
+        let value = self.parse_expression(Precedence::Lowest)?;
+
+        if !self.expect_peek(TokenType::Keyword(KeywordType::Do)) {
+            return None;
+        }
+
+        let mut arms = Vec::new();
+        while self.peek_token.ttype != TokenType::Keyword(KeywordType::End) {
+            if self.peek_token.ttype == TokenType::Eof {
+                return self.unclosed_match();
+            }
+
+            self.next_token();
+            let Some(pattern) = self.parse_pattern() else {
+                self.error("invalid pattern");
+                return None;
+            };
+
+            let mut guard = None;
+            if self.peek_token.ttype == TokenType::Keyword(KeywordType::If) {
+                self.next_token();
+                self.next_token();
+                match self.parse_expression(Precedence::Lowest) {
+                    Some(expr) => guard = Some(expr),
+                    None => return self.unclosed_match(),
+                }
+            }
+
+            if !self.expect_peek(TokenType::Arrow) {
+                if self.peek_token.ttype != TokenType::Eof {
+                    self.error("expected `->` after match pattern");
+                    return None;
+                }
+                return self.unclosed_match();
+            }
+            let arm_token = self.current_token.clone();
+
+            self.next_token();
+            let Some(body) = self.parse_expression(Precedence::Lowest) else {
+                return self.unclosed_match();
+            };
+
+            if matches!(
+                self.peek_token.ttype,
+                TokenType::Comma | TokenType::Semicolon
+            ) {
+                self.next_token();
+            }
+
+            arms.push(MatchArm {
+                token: arm_token,
+                pattern,
+                guard,
+                body,
+            });
+        }
+        self.next_token();
+
+        Some(Expression::Match {
+            token,
+            value: Box::new(value),
+            arms,
+        })
+    }
+
+    /// Reports a match that runs into the end of the input. Other failures
+    /// inside an arm return `None` without a message of their own.
+    fn unclosed_match(&mut self) -> Option<Expression> {
+        if self.current_token.ttype == TokenType::Eof || self.peek_token.ttype == TokenType::Eof {
+            self.error("expected `end` to close match");
+        }
+        None
+    }
+
+    pub(crate) fn parse_pattern(&mut self) -> Option<Pattern> {
+        match self.current_token.ttype {
+            TokenType::Ident if self.current_token.literal == "_" => Some(Pattern::Wildcard),
+            TokenType::Ident => Some(Pattern::Binding(Identifier {
+                token: self.current_token.clone(),
+                value: self.current_token.literal.clone(),
+            })),
+            TokenType::LBracket => self.parse_array_pattern(),
+            TokenType::LBrace => self.parse_hash_pattern(),
+            _ => {
+                let start = self.parse_literal_pattern()?;
+
+                if self.peek_token.ttype != TokenType::Keyword(KeywordType::To) {
+                    return Some(Pattern::Value(start));
+                }
+
+                self.next_token();
+                self.next_token();
+                let end = self.parse_literal_pattern()?;
+
+                match (&start, &end) {
+                    (
+                        Literal::Integer(_) | Literal::Float(_),
+                        Literal::Integer(_) | Literal::Float(_),
+                    ) => Some(Pattern::Range(start, end)),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// A number, optionally negative, a string, a boolean or null.
+    fn parse_literal_pattern(&mut self) -> Option<Literal> {
+        let negative = self.current_token.ttype == TokenType::Sub;
+        if negative && !self.expect_peek(TokenType::Number) {
+            return None;
+        }
+
+        let literal = match self.current_token.ttype {
+            TokenType::Number => match self.parse_integer_literal()? {
+                Expression::Literal(literal) => literal,
+                _ => return None,
+            },
+            TokenType::String => Literal::String(self.current_token.literal.clone()),
+            TokenType::Keyword(KeywordType::True) => Literal::Boolean(true),
+            TokenType::Keyword(KeywordType::False) => Literal::Boolean(false),
+            TokenType::Keyword(KeywordType::Null) => Literal::Null,
+            _ => return None,
+        };
+
+        Some(match (negative, literal) {
+            (true, Literal::Integer(int)) => Literal::Integer(-int),
+            (true, Literal::Float(float)) => Literal::Float(-float),
+            (_, literal) => literal,
+        })
+    }
+
+    /// `[first, second, ...rest]`
+    fn parse_array_pattern(&mut self) -> Option<Pattern> {
+        let mut items = Vec::new();
+        let mut rest = None;
+
+        while self.peek_token.ttype != TokenType::RBracket {
+            self.next_token();
+
+            if self.current_token.ttype == TokenType::Ellipsis {
+                self.next_token();
+                match self.parse_pattern()? {
+                    pattern @ (Pattern::Binding(_) | Pattern::Wildcard) => {
+                        rest = Some(Box::new(pattern))
+                    }
+                    _ => return None,
+                }
+                break;
+            }
+
+            items.push(self.parse_pattern()?);
+
+            if self.peek_token.ttype != TokenType::RBracket && !self.expect_peek(TokenType::Comma) {
+                return None;
+            }
+        }
+
+        if !self.expect_peek(TokenType::RBracket) {
+            return None;
+        }
+
+        Some(Pattern::Array { items, rest })
+    }
+
+    /// `{"code": 0, "stdout": out}`, where `{name}` is short for `{"name": name}`
+    fn parse_hash_pattern(&mut self) -> Option<Pattern> {
+        let mut entries = Vec::new();
+
+        while self.peek_token.ttype != TokenType::RBrace {
+            self.next_token();
+
+            let key = self.current_token.literal.clone();
+            match self.current_token.ttype {
+                TokenType::String => {
+                    if !self.expect_peek(TokenType::Colon) {
+                        return None;
+                    }
+                    self.next_token();
+                    entries.push((key, self.parse_pattern()?));
+                }
+                TokenType::Ident => {
+                    let name = Identifier {
+                        token: self.current_token.clone(),
+                        value: key.clone(),
+                    };
+                    entries.push((key, Pattern::Binding(name)));
+                }
+                _ => return None,
+            }
+
+            if self.peek_token.ttype != TokenType::RBrace && !self.expect_peek(TokenType::Comma) {
+                return None;
+            }
+        }
+
+        if !self.expect_peek(TokenType::RBrace) {
+            return None;
+        }
+
+        Some(Pattern::Hash(entries))
+    }
+}
@@ -1,6 +1,9 @@
 #[derive(PartialOrd, PartialEq)]
 pub(crate) enum Precedence {
     Lowest,
+    Or,
+    And,
+    Coalesce,
     Equals,
     LessGreater,
     Sum,
@@ -1,12 +1,13 @@
 use super::precedence::Precedence;
 use super::Parser;
-use crate::ast::{BlockStatement, Identifier, Statement};
-use crate::lexer::{KeywordType, PrimitiveKind, TokenType};
+use crate::ast::{BlockStatement, Expression, Identifier, Statement};
+use crate::lexer::{KeywordType, Token, TokenType};
 
 impl Parser {
     pub(crate) fn parse_statement(&mut self) -> Option<Statement> {
         match self.current_token.ttype {
-            TokenType::Keyword(KeywordType::Let) => self.parse_let_statement(),
+            TokenType::Keyword(KeywordType::Let) => self.parse_let_statement(false),
+            TokenType::Keyword(KeywordType::Const) => self.parse_let_statement(true),
             TokenType::Keyword(KeywordType::Return) => self.parse_return_statement(),
             TokenType::Ident => {
                 if self.peek_token.ttype == TokenType::Assign {
@@ -31,7 +32,7 @@ impl Parser {
 
         self.next_token();
 
-        let value = self.parse_expression(Precedence::Lowest).unwrap();
+        let value = self.parse_expression(Precedence::Lowest)?;
 
         if self.peek_token.ttype == TokenType::Semicolon {
             self.next_token();
@@ -45,7 +46,24 @@ impl Parser {
     }
 
     fn parse_expression_statement(&mut self) -> Option<Statement> {
-        let expr = self.parse_expression(Precedence::Lowest);
+        let token = self.current_token.clone();
+
+        // Stop short of `=` so `a[0] = v` and `a.b = v` can become assignments
+        let expr = self.parse_expression(Precedence::Equals);
+
+        if self.peek_token.ttype == TokenType::Assign {
+            if let Some(
+                target @ (Expression::IndexExpression { .. } | Expression::DotNotation { .. }),
+            ) = expr
+            {
+                return self.parse_assign_statement(token, target);
+            }
+        }
+
+        let expr = match expr {
+            Some(_) => self.parse_infix_operators(expr, Precedence::Lowest),
+            None => None,
+        };
 
         if self.peek_token.ttype == TokenType::Semicolon {
             self.next_token();
@@ -61,32 +79,51 @@ impl Parser {
         }
     }
 
-    fn parse_return_statement(&mut self) -> Option<Statement> {
-        let token = self.current_token.clone();
+    fn parse_assign_statement(&mut self, token: Token, target: Expression) -> Option<Statement> {
+        if !self.expect_peek(TokenType::Assign) {
+            return None;
+        }
 
         self.next_token();
 
-        let value = self.parse_expression(Precedence::Lowest).unwrap();
+        let value = self.parse_expression(Precedence::Lowest)?;
 
         if self.peek_token.ttype == TokenType::Semicolon {
             self.next_token();
         }
 
-        Some(Statement::Return { token, value })
+        Some(Statement::Assign {
+            token,
+            target,
+            value,
+        })
     }
 
-    fn parse_let_statement(&mut self) -> Option<Statement> {
-        let primitive_kind: Option<PrimitiveKind>;
+    fn parse_return_statement(&mut self) -> Option<Statement> {
+        let token = self.current_token.clone();
 
-        primitive_kind = match &self.peek_token.ttype {
-            TokenType::Keyword(KeywordType::Primitive(p)) => Some(p.clone()),
-            _ => None,
-        };
+        self.next_token();
+
+        let value = self.parse_expression(Precedence::Lowest)?;
 
-        if primitive_kind.is_some() {
+        if self.peek_token.ttype == TokenType::Semicolon {
             self.next_token();
         }
 
+        Some(Statement::Return { token, value })
+    }
+
+    /// `let` and `const` only differ in whether the binding can be reassigned.
+    fn parse_let_statement(&mut self, constant: bool) -> Option<Statement> {
+        if matches!(
+            self.peek_token.ttype,
+            TokenType::LBracket | TokenType::LBrace
+        ) {
+            return self.parse_destructure_statement(constant);
+        }
+
+        let primitive_kind = self.parse_primitive_kind();
+
         if !self.expect_peek(TokenType::Ident) {
             return None;
         }
@@ -102,7 +139,7 @@ impl Parser {
 
         self.next_token();
 
-        let value = self.parse_expression(Precedence::Lowest).unwrap();
+        let value = self.parse_expression(Precedence::Lowest)?;
 
         if self.peek_token.ttype == TokenType::Semicolon {
             self.next_token();
@@ -113,6 +150,36 @@ impl Parser {
             name,
             value,
             value_kind: primitive_kind.clone(),
+            constant,
+        })
+    }
+
+    /// `let [a, b] = arr` or `let {name, age} = person`
+    fn parse_destructure_statement(&mut self, constant: bool) -> Option<Statement> {
+        let token = self.current_token.clone();
+
+        self.next_token();
+        let Some(pattern) = self.parse_pattern() else {
+            self.error("invalid pattern");
+            return None;
+        };
+
+        if !self.expect_peek(TokenType::Assign) {
+            return None;
+        }
+
+        self.next_token();
+        let value = self.parse_expression(Precedence::Lowest)?;
+
+        if self.peek_token.ttype == TokenType::Semicolon {
+            self.next_token();
+        }
+
+        Some(Statement::Destructure {
+            token,
+            pattern,
+            value,
+            constant,
         })
     }
 
@@ -0,0 +1,202 @@
+#[cfg(test)]
+mod check_tests {
+    use std::process::Command;
+
+    use tammr::check::Checker;
+    use tammr::lexer::Lexer;
+    use tammr::Parser;
+
+    #[test]
+    fn test_check_reports_errors() {
+        let tests = vec![
+            (
+                r#"let s = "a" + 1;"#,
+                "line 1: Cannot apply `+` to str and number",
+            ),
+            (
+                "let add = function(a, b) { a + b };\nadd(1)",
+                "line 2: Wrong number of arguments to `add`: expected 2, got 1",
+            ),
+            ("let n = 5; n()", "line 1: Cannot call number"),
+            ("len(missing)", "line 1: Unknown identifier `missing`"),
+            ("missing = 1", "line 1: Unknown identifier `missing`"),
+            (r#""abc".size"#, "line 1: No property named size on String"),
+            ("[1, 2].frob()", "line 1: No method named frob on Array"),
+            (
+                r#"let number port = "80""#,
+                "line 1: Type mismatch for `port`: expected number, got str",
+            ),
+            (
+                "let str s = \"a\";\ns = 1",
+                "line 2: Type mismatch for `s`: expected str, got number",
+            ),
+            ("-\"a\"", "line 1: Cannot apply `-` to str"),
+            (
+                "let f = function(str s) { s };\nf(1)",
+                "line 2: Type mismatch for argument 1 to `f`: expected str, got number",
+            ),
+            (
+                "let f = function(str s) { s.frob() }",
+                "line 1: No method named frob on String",
+            ),
+            (
+                "let f = function() -> str { return 1 }",
+                "line 1: Type mismatch for return value: expected str, got number",
+            ),
+            (
+                "let f = function(number n) -> str {\n  n }",
+                "line 2: Type mismatch for return value: expected str, got number",
+            ),
+            (
+                "let f = function() -> number { 1 }; f() + \"a\"",
+                "line 1: Cannot apply `+` to number and str",
+            ),
+            ("let n = 1; let n = 2", "line 1: Cannot redeclare `n`"),
+            ("const n = 1; n = 2", "line 1: Cannot reassign constant `n`"),
+            (
+                "if (true) { let inner = 1 }; inner",
+                "line 1: Unknown identifier `inner`",
+            ),
+            (
+                "println(z);\nlet z = 1;",
+                "line 1: `z` is used before it is declared",
+            ),
+            (
+                "if (true) { z = 2 }; let z = 1",
+                "line 1: `z` is used before it is declared",
+            ),
+            (
+                "let f = function() { let a = b; let b = 1 }",
+                "line 1: `b` is used before it is declared",
+            ),
+            (
+                "let [a, b] = [1, 2]; let a = 3",
+                "line 1: Cannot redeclare `a`",
+            ),
+            (
+                "const {name} = {}; name = 1",
+                "line 1: Cannot reassign constant `name`",
+            ),
+            ("1 < \"a\"", "line 1: Cannot apply `<` to number and str"),
+            (
+                "let n = int(\"1\") + str(2)",
+                "line 1: Cannot apply `+` to number and str",
+            ),
+            ("match 1 do n -> n end; n", "line 1: Unknown identifier `n`"),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program = parser.parse_program().expect("Could not parse");
+
+            let diagnostics: Vec<String> = Checker::new()
+                .check(&program)
+                .iter()
+                .map(|diagnostic| diagnostic.to_string())
+                .collect();
+            assert_eq!(diagnostics, vec![expected.to_string()], "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_check_strict_conditions() {
+        let tests = vec![
+            (
+                "if (1) { 2 }",
+                "line 1: Condition must be a bool, got number",
+            ),
+            (
+                "match 1 do n if 1 -> n end",
+                "line 1: Match guard must be a bool, got number",
+            ),
+            ("not \"a\"", "line 1: Cannot apply `not` to str"),
+            (
+                "let b = true and 1",
+                "line 1: Cannot apply `and` to bool and number",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program = parser.parse_program().expect("Could not parse");
+
+            let mut checker = Checker::new();
+            checker.set_strict(true);
+            let diagnostics: Vec<String> = checker
+                .check(&program)
+                .iter()
+                .map(|diagnostic| diagnostic.to_string())
+                .collect();
+            assert_eq!(diagnostics, vec![expected.to_string()], "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_check_command_without_a_program() {
+        let tammr = env!("CARGO_BIN_EXE_tammr");
+
+        let output = Command::new(tammr)
+            .arg("check")
+            .output()
+            .expect("Could not run tammr");
+        assert_eq!(output.status.code(), Some(2));
+        assert!(String::from_utf8_lossy(&output.stderr).starts_with("usage: tammr check"));
+
+        let output = Command::new(tammr)
+            .args(["check", "missing.tmr"])
+            .output()
+            .expect("Could not run tammr");
+        assert_eq!(output.status.code(), Some(1));
+        assert!(
+            String::from_utf8_lossy(&output.stderr).starts_with("missing.tmr: cannot read file")
+        );
+    }
+
+    #[test]
+    fn test_check_accepts_valid_programs() {
+        let tests = vec![
+            "let a = 1 + 2.5; let b = a * 2; b > 1",
+            r#"let s = "a" + "b"; s.length + s.toUpper().length"#,
+            "let f = function() { later }; let later = 1; f()",
+            "let f = function(number n) -> str { if (n > 0) { return \"+\" }; str(n) }",
+            // The outer `n` until the inner one is declared
+            "let n = 1; if (true) { let m = n; let n = 2 }",
+            // Reassigned names may change type, so they are not tracked
+            r#"let x = 1; x = "one"; x + "!""#,
+            // Hash fields and member functions are data
+            r#"let h = {"f": function() { 1 }}; h.f() + h.missing"#,
+            "let g = function(f) { f(1, 2) }; g(len)",
+            r#"if (true) { let y = 1 } else { let y = "a" }; let y = true"#,
+            "let n = 1; if (true) { let n = 2; n = 3 }",
+            r#"/a+/.test("aa") == true"#,
+            "let xs = [3, 1]; xs[0:1].length",
+            r#"let cfg = {}; let port = cfg.port ?? 80; port + 1"#,
+            "let s = null ?? \"a\"; s.toUpper()",
+            "let [first, ...rest] = [1, 2]; first + len(rest)",
+            "let f = function() { rest }; let {rest} = {\"rest\": 1}; f()",
+            "match [1] do [x] if x > 0 -> x, _ -> 0 end",
+            "let s = type(1) + str(2); let b = 1 is number",
+            // Conditions count by truthiness outside strict mode
+            "if (1) { 2 }; let name = \"\" or \"anon\"; not [] and name",
+            "match 1 do n if n -> n end",
+            "let same = [1] == \"a\" or null != 1; \"a\" < \"b\" and [1] > [0]",
+        ];
+
+        for input in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program = parser.parse_program().expect("Could not parse");
+
+            let diagnostics = Checker::new().check(&program);
+            assert!(diagnostics.is_empty(), "{}: {:?}", input, diagnostics);
+        }
+    }
+}
@@ -2,48 +2,1466 @@
 mod test {
     use tammr::ast::Program;
     use tammr::lexer::Lexer;
-    use tammr::object::Object;
+    use tammr::object::{HashKey, Object};
     use tammr::parser::Parser;
 
     use tammr::eval::Evaluator;
 
+    use std::sync::{Mutex, MutexGuard};
+
+    /// `setEnv` and `unsetEnv` change the environment of the whole test
+    /// binary, which processes started by other tests inherit, so tests that
+    /// touch either take turns
+    static PROCESS_ENV: Mutex<()> = Mutex::new(());
+
+    fn lock_process_env() -> MutexGuard<'static, ()> {
+        PROCESS_ENV
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    #[test]
+    fn test_dot_notation() {
+        let tests = vec![
+            (
+                r#"
+                let kv person = {"name": "Joe"};
+                person.name
+                "#,
+                Object::String("Joe".to_string()),
+            ),
+            (
+                r#"
+            let person = {"name": "Joe", "age": 90};
+            person.age
+            "#,
+                Object::Integer(90),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            // create new lexer with input
+            let mut l = Lexer::new(input.to_string());
+            // generate tokens from lexer
+            let tokens = l.gen_tokens();
+
+            // create new parser with tokens
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            // parse program from parser
+            let program: Option<Program> = parser.parse_program();
+
+            // if program exists
+            if let Some(program) = program {
+                // create new evaluator
+                let mut evaluator = Evaluator::new();
+                // evaluate program
+                if let Some(result) = evaluator.eval(&program) {
+                    // assert that result is equal to expected
+                    println!("{} - {}", result, expected);
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_builtin_exec() {
+        let _env = lock_process_env();
+
+        let tests = vec![
+            (
+                r#"exec("echo hello").stdout"#,
+                Object::String("hello\n".to_string()),
+            ),
+            (
+                r#"exec(["printf", "%s", "no 'quoting' needed"]).stdout"#,
+                Object::String("no 'quoting' needed".to_string()),
+            ),
+            (
+                r#"exec("echo oops >&2").stderr"#,
+                Object::String("oops\n".to_string()),
+            ),
+            (r#"exec("exit 3").code"#, Object::Integer(3)),
+            (
+                r#"exec("cat", {"stdin": "from stdin"}).stdout"#,
+                Object::String("from stdin".to_string()),
+            ),
+            (
+                r#"exec("pwd", {"cwd": "/"}).stdout"#,
+                Object::String("/\n".to_string()),
+            ),
+            (
+                r#"exec(["sh", "-c", "echo $GREETING"], {"env": {"GREETING": "hi"}}).stdout"#,
+                Object::String("hi\n".to_string()),
+            ),
+            (
+                r#"exec("echo out; echo err >&2; exit 2", {"check": true})"#,
+                Object::Error(
+                    "`echo out; echo err >&2; exit 2` exited with code 2\nstdout: out\nstderr: err"
+                        .to_string(),
+                ),
+            ),
+            (
+                r#"exec("sleep 5", {"timeout": 50})"#,
+                Object::Error("`sleep 5` timed out after 50ms\nstdout: \nstderr: ".to_string()),
+            ),
+            (
+                r#"exec("true", {"timeout": 0})"#,
+                Object::Error(
+                    "exec: Option `timeout` must be a positive number of milliseconds, got 0"
+                        .to_string(),
+                ),
+            ),
+            (
+                r#"exec("true", {"shell": true})"#,
+                Object::Error("exec: Unknown option `shell`".to_string()),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+
+                if let Some(result) = evaluator.eval(&program) {
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_builtin_pipe_and_spawn() {
+        let _env = lock_process_env();
+
+        let tests = vec![
+            (
+                r#"pipe(["printf", "b\na\nc\n"], ["sort"]).stdout"#,
+                Object::String("a\nb\nc\n".to_string()),
+            ),
+            (
+                r#"pipe("echo hi", "tr a-z A-Z", "tr H J").stdout"#,
+                Object::String("JI\n".to_string()),
+            ),
+            (
+                r#"pipe(["cat"], ["grep", "fix"], {"stdin": "fix one\nfeat\nfix two\n"}).stdout"#,
+                Object::String("fix one\nfix two\n".to_string()),
+            ),
+            (r#"pipe("exit 1", "exit 4").code"#, Object::Integer(4)),
+            (r#"spawn("true").pid > 0"#, Object::Boolean(true)),
+            (
+                r#"let p = spawn("echo done"); p.wait().stdout"#,
+                Object::String("done\n".to_string()),
+            ),
+            (
+                r#"let p = spawn("sleep 5"); p.kill(); p.wait().code"#,
+                Object::Null,
+            ),
+            (
+                r#"spawn(["printf", "a\nb\n"]).lines(function(line) { line }).code"#,
+                Object::Integer(0),
+            ),
+            (
+                r#"
+                spawn("echo first; echo second; echo third").lines(function(line) {
+                    if (line == "second") {
+                        exec("exit 1", {"check": true})
+                    } else {
+                        line
+                    }
+                })
+                "#,
+                Object::Error("`exit 1` exited with code 1\nstdout: \nstderr: ".to_string()),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+
+                if let Some(result) = evaluator.eval(&program) {
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dropped_spawn_is_reaped() {
+        let _env = lock_process_env();
+
+        let input = r#"spawn("sleep 30").pid"#;
+
+        let mut l = Lexer::new(input.to_string());
+        let tokens = l.gen_tokens();
+
+        let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+        let program = parser.parse_program().expect("Could not parse");
+
+        let mut evaluator = Evaluator::new();
+        let pid = match evaluator.eval(&program) {
+            Some(Object::Integer(pid)) => pid,
+            other => panic!("Expected a pid, got {:?}", other),
+        };
+
+        // `kill -0` also succeeds for a zombie, so this fails unless the
+        // child was both killed and reaped when its handle was dropped
+        let alive = std::process::Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .stderr(std::process::Stdio::null())
+            .status()
+            .expect("Could not run kill")
+            .success();
+
+        assert!(!alive, "process {} is still around", pid);
+    }
+
+    #[test]
+    fn test_builtin_env() {
+        let _env = lock_process_env();
+
+        let tests = vec![
+            (
+                r#"setEnv("TAMMR_TEST_SET", "value"); getEnv("TAMMR_TEST_SET")"#,
+                Object::String("value".to_string()),
+            ),
+            (r#"getEnv("TAMMR_TEST_MISSING")"#, Object::Null),
+            (
+                r#"getEnv("TAMMR_TEST_MISSING", "fallback")"#,
+                Object::String("fallback".to_string()),
+            ),
+            (
+                r#"setEnv("TAMMR_TEST_NUMBER", 8080); getEnv("TAMMR_TEST_NUMBER")"#,
+                Object::String("8080".to_string()),
+            ),
+            (
+                r#"setEnv("TAMMR_TEST_UNSET", "x"); unsetEnv("TAMMR_TEST_UNSET"); getEnv("TAMMR_TEST_UNSET")"#,
+                Object::Null,
+            ),
+            (
+                r#"setEnv("TAMMR_TEST_SNAPSHOT", "snap"); env()["TAMMR_TEST_SNAPSHOT"]"#,
+                Object::String("snap".to_string()),
+            ),
+            (
+                r#"setEnv("TAMMR_TEST_MAP", "map"); envMap().TAMMR_TEST_MAP"#,
+                Object::String("map".to_string()),
+            ),
+            (
+                r#"setEnv("TAMMR_TEST=BAD", "x")"#,
+                Object::Error("Invalid environment variable: TAMMR_TEST=BAD".to_string()),
+            ),
+            (
+                r#"setEnv("TAMMR_TEST_LIST", [1])"#,
+                Object::Error(
+                    "Arguments to `setEnv` must be STRING and STRING, INTEGER or BOOLEAN, got TAMMR_TEST_LIST and [1]"
+                        .to_string(),
+                ),
+            ),
+            (
+                r#"unsetEnv(json.parse("\"TAMMR\\u0000TEST\""))"#,
+                Object::Error("Invalid environment variable: TAMMR\0TEST".to_string()),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+
+                if let Some(result) = evaluator.eval(&program) {
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_env_reaches_child_processes() {
+        let _env = lock_process_env();
+
+        let input = r#"
+            setEnv("TAMMR_TEST_CHILD", "inherited");
+            exec("echo $TAMMR_TEST_CHILD").stdout
+        "#;
+
+        let mut l = Lexer::new(input.to_string());
+        let tokens = l.gen_tokens();
+
+        let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+        let program: Option<Program> = parser.parse_program();
+
+        if let Some(program) = program {
+            let mut evaluator = Evaluator::new();
+
+            if let Some(result) = evaluator.eval(&program) {
+                assert_eq!(result, Object::String("inherited\n".to_string()));
+            } else {
+                panic!("No result");
+            }
+        }
+    }
+
+    #[test]
+    fn test_json() {
+        let tests = vec![
+            (
+                r#"json.parse("{\"name\": \"tammr\", \"tags\": [1, 2.5, null]}").tags[1]"#,
+                Object::Float(2.5),
+            ),
+            (
+                r#"json.parse("{\"a\": {\"b\": {\"c\": -3}}}").a.b.c"#,
+                Object::Integer(-3),
+            ),
+            (r#"json.parse("[true, null]")[1]"#, Object::Null),
+            (
+                r#"json.parse("\"caf\\u00e9 \\ud83d\\ude00\"")"#,
+                Object::String("café 😀".to_string()),
+            ),
+            (r#"json.parse("1e3")"#, Object::Float(1000.0)),
+            (
+                r#"json.parse("{\"a\": 1,\n \"b\" 2}")"#,
+                Object::Error(
+                    "JSON parse error at line 2, column 6: expected ':', found '2'".to_string(),
+                ),
+            ),
+            (
+                r#"json.parse("[1, 2")"#,
+                Object::Error(
+                    "JSON parse error at line 1, column 6: expected ',' or ']' in array"
+                        .to_string(),
+                ),
+            ),
+            (
+                r#"json.stringify({"b": 1, "a": [true, json.parse("null"), 1.5], "c": "q\"uote"})"#,
+                Object::String(r#"{"b":1,"a":[true,null,1.5],"c":"q\"uote"}"#.to_string()),
+            ),
+            (
+                r#"json.stringify({"a": [1, 2], "b": {}}, 2)"#,
+                Object::String("{\n  \"a\": [\n    1,\n    2\n  ],\n  \"b\": {}\n}".to_string()),
+            ),
+            (
+                r#"let text = json.stringify({"z": [1, {"y": 2.0}], "x": false}); json.stringify(json.parse(text)) == text"#,
+                Object::Boolean(true),
+            ),
+            (
+                r#"json.parse("[".repeat(1000))"#,
+                Object::Error(
+                    "JSON parse error at line 1, column 129: nested deeper than 128 levels"
+                        .to_string(),
+                ),
+            ),
+            (
+                r#"len(json.parse("[".repeat(128) + "]".repeat(128)))"#,
+                Object::Integer(1),
+            ),
+            (
+                r#"json.stringify({"f": len})"#,
+                Object::Error("Cannot serialize builtinfunction to JSON".to_string()),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+
+                if let Some(result) = evaluator.eval(&program) {
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_toml() {
+        let tests = vec![
+            (
+                r#"
+                let cfg = toml.parse('title = "demo"\n\n[server]\nport = 8080 # http\nhosts = ["a", "b"]\n');
+                cfg.server.port
+                "#,
+                Object::Integer(8080),
+            ),
+            (
+                r#"toml.parse('[[deploy]]\nname = "web"\n\n[[deploy]]\nname = "db"\n').deploy[1].name"#,
+                Object::String("db".to_string()),
+            ),
+            (
+                r#"toml.parse('a.b = { c = 1.5, d = [1, 2,] }').a.b.c"#,
+                Object::Float(1.5),
+            ),
+            (
+                r#"toml.parse('n = 1_000\nh = 0xff').h"#,
+                Object::Integer(255),
+            ),
+            (
+                r#"toml.parse("path = 'C:\\temp'\nwhen = 1979-05-27T07:32:00Z").when"#,
+                Object::String("1979-05-27T07:32:00Z".to_string()),
+            ),
+            (
+                r#"toml.parse('a = 1\na = 2')"#,
+                Object::Error("TOML parse error at line 2: duplicate key `a`".to_string()),
+            ),
+            (
+                r#"toml.parse('a = [1, 2')"#,
+                Object::Error(
+                    "TOML parse error at line 1, column 10: expected ',' or ']' in array"
+                        .to_string(),
+                ),
+            ),
+            (
+                r#"toml.parse("a = " + "[".repeat(1000))"#,
+                Object::Error(
+                    "TOML parse error at line 1, column 133: nested deeper than 128 levels"
+                        .to_string(),
+                ),
+            ),
+            (
+                r#"toml.stringify({"title": "x", "server": {"port": 80}, "jobs": [{"n": 1}, {"n": 2}]})"#,
+                Object::String(
+                    "title = \"x\"\n\n[server]\nport = 80\n\n[[jobs]]\nn = 1\n\n[[jobs]]\nn = 2\n"
+                        .to_string(),
+                ),
+            ),
+            (
+                r#"
+                let cfg = {"name": "web", "ports": [80, 443], "db": {"url": "pg://"}};
+                let text = toml.stringify(cfg);
+                toml.stringify(toml.parse(text)) == text
+                "#,
+                Object::Boolean(true),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+
+                if let Some(result) = evaluator.eval(&program) {
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_yaml() {
+        let tests = vec![
+            (
+                r#"
+                let cfg = yaml.parse('server:\n  port: 8080\n  host: localhost\n');
+                cfg.server.port
+                "#,
+                Object::Integer(8080),
+            ),
+            (
+                r#"yaml.parse('deploy:\n  - name: web\n    replicas: 2\n  - name: db\n').deploy[0].replicas"#,
+                Object::Integer(2),
+            ),
+            (
+                r#"yaml.parse('tags:\n- a\n- b\n').tags[1]"#,
+                Object::String("b".to_string()),
+            ),
+            (r#"yaml.parse('a: ~\nb: 1').a"#, Object::Null),
+            (r#"yaml.parse('v: 1.5 # comment').v"#, Object::Float(1.5)),
+            (
+                r#"yaml.parse("s: 'it''s'").s"#,
+                Object::String("it's".to_string()),
+            ),
+            (
+                r#"yaml.parse('ports: [80, 443]\nenv: {debug: true, name: "x"}').env.debug"#,
+                Object::Boolean(true),
+            ),
+            (
+                r#"yaml.parse('script: |\n  make\n  make test\nnext: 1\n').script"#,
+                Object::String("make\nmake test\n".to_string()),
+            ),
+            (
+                r#"yaml.parse('text: >-\n  one\n  two\n').text"#,
+                Object::String("one two".to_string()),
+            ),
+            (
+                r#"yaml.parse('a: 1\n  b: 2')"#,
+                Object::Error("YAML parse error at line 2: unexpected indentation".to_string()),
+            ),
+            (
+                r#"yaml.parse('a:\n\tb: 1')"#,
+                Object::Error(
+                    "YAML parse error at line 2: tabs are not allowed in indentation".to_string(),
+                ),
+            ),
+            (
+                r#"yaml.parse('make: |\n  \tcc main.c\n').make"#,
+                Object::String("\tcc main.c\n".to_string()),
+            ),
+            (
+                r#"yaml.parse("a: " + "[".repeat(1000))"#,
+                Object::Error(
+                    "YAML parse error at line 1: nested deeper than 128 levels".to_string(),
+                ),
+            ),
+            (
+                r#"yaml.parse("- ".repeat(1000) + "x")"#,
+                Object::Error(
+                    "YAML parse error at line 1: nested deeper than 128 levels".to_string(),
+                ),
+            ),
+            (
+                r#"yaml.parse('a: &x 1')"#,
+                Object::Error(
+                    "YAML parse error at line 1: anchors, aliases and tags are not supported"
+                        .to_string(),
+                ),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+
+                if let Some(result) = evaluator.eval(&program) {
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_hash_index() {
+        let tests = vec![
+            (
+                r#"
+                let kv myHash = {"one": 1, "two": 2};
+                myHash["one"]
+                "#,
+                Object::Integer(1),
+            ),
+            (
+                r#"
+                let kv myHash = {"one": 1, "two": 2};
+                myHash["two"]
+                "#,
+                Object::Integer(2),
+            ),
+            (
+                r#"
+                let kv myHash = {"one": 1, "two": 2};
+                myHash["three"]
+                "#,
+                Object::Null,
+            ),
+            (
+                r#"
+                let kv myHash = {"one": 1, "two": 2};
+                myHash["one"] + myHash["two"]
+                "#,
+                Object::Integer(3),
+            ),
+            (r#"{1: "a", 2: "b"}[2]"#, Object::String("b".to_string())),
+            (
+                r#"{true: "yes", false: "no"}[1 > 2]"#,
+                Object::String("no".to_string()),
+            ),
+            (
+                r#"{1: "int", "1": "string"}["1"]"#,
+                Object::String("string".to_string()),
+            ),
+            (r#"{"a": 1, "b": 2, "a": 3}["a"]"#, Object::Integer(3)),
+            (r#"len({"a": 1, "b": 2, "a": 3})"#, Object::Integer(2)),
+            (
+                r#"{[1]: "array"}"#,
+                Object::Error("Unusable as hash key: Array".to_string()),
+            ),
+            (
+                r#"{"a": 1}[1.5]"#,
+                Object::Error("Unusable as hash key: Float".to_string()),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            // create new lexer with input
+            let mut l = Lexer::new(input.to_string());
+            // generate tokens from lexer
+            let tokens = l.gen_tokens();
+
+            // create new parser with tokens
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            // parse program from parser
+            let program: Option<Program> = parser.parse_program();
+
+            // if program exists
+            if let Some(program) = program {
+                // create new evaluator
+                let mut evaluator = Evaluator::new();
+                // evaluate program
+                if let Some(result) = evaluator.eval(&program) {
+                    // assert that result is equal to expected
+                    println!("{} - {}", result, expected);
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_index_and_field_assignment() {
+        let tests = vec![
+            ("let xs = [1, 2, 3]; xs[0] = 10; xs[0]", Object::Integer(10)),
+            (
+                "let xs = [1, 2, 3]; xs[-1] = 7; xs",
+                Object::array(vec![
+                    Object::Integer(1),
+                    Object::Integer(2),
+                    Object::Integer(7),
+                ]),
+            ),
+            (
+                r#"let h = {"a": 1}; h["b"] = 2; h["a"] + h.b"#,
+                Object::Integer(3),
+            ),
+            (r#"let h = {"a": 1}; h.a = 5; h["a"]"#, Object::Integer(5)),
+            (
+                r#"let h = {}; h[1] = "one"; h[1]"#,
+                Object::String("one".to_string()),
+            ),
+            (
+                r#"
+                let cfg = {"server": {"port": 8080, "hosts": ["a", "b"]}};
+                cfg.server.port = 80;
+                cfg.server.hosts[1] = "c";
+                cfg.server.port + len(cfg.server.hosts[1])
+                "#,
+                Object::Integer(81),
+            ),
+            (
+                "let xs = [1, 2, 3]; xs[3] = 4",
+                Object::Error("Index out of range: 3 (length 3)".to_string()),
+            ),
+            (
+                "let xs = [1, 2, 3]; xs[-4] = 4",
+                Object::Error("Index out of range: -4 (length 3)".to_string()),
+            ),
+            (
+                r#"let cfg = {}; cfg.server.port = 80"#,
+                Object::Error("Key not found: server".to_string()),
+            ),
+            (
+                r#"let cfg = {"server": {}}; cfg["server"]["tls"].enabled = true"#,
+                Object::Error("Key not found: tls".to_string()),
+            ),
+            (
+                r#"let cfg = {"server": null}; cfg.server.port = 80"#,
+                Object::Error("Cannot assign into Null".to_string()),
+            ),
+            (
+                r#"let s = "abc"; s[0] = "x""#,
+                Object::Error("Cannot assign into String".to_string()),
+            ),
+            (
+                "missing[0] = 1",
+                Object::Error("Identifier not found (eval_identifier): missing".to_string()),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+                if let Some(result) = evaluator.eval(&program) {
+                    println!("{} - {}", result, expected);
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_type_hints() {
+        let tests = vec![
+            (
+                r#"let str name = "tammr"; name"#,
+                Object::String("tammr".to_string()),
+            ),
+            ("let number n = 1; n = 2.5; n", Object::Float(2.5)),
+            (
+                "let arr xs = [1]; let kv h = {}; let bool b = true; b",
+                Object::Boolean(true),
+            ),
+            (
+                r#"let number port = "80""#,
+                Object::Error(
+                    "Type mismatch for `port`: expected number, got str at line 1".to_string(),
+                ),
+            ),
+            (
+                "let str s = \"a\";\ns = [1]",
+                Object::Error("Type mismatch for `s`: expected str, got arr at line 2".to_string()),
+            ),
+            (
+                "let kv h = {}; let f = function() { h = 1 }; f()",
+                Object::Error(
+                    "Type mismatch for `h`: expected kv, got number at line 1".to_string(),
+                ),
+            ),
+            (
+                "let bool b = len",
+                Object::Error(
+                    "Type mismatch for `b`: expected bool, got function at line 1".to_string(),
+                ),
+            ),
+            // Shadowing without a hint drops it
+            (
+                r#"let number n = 1; if (true) { let n = "one"; n = true; n }"#,
+                Object::Boolean(true),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+                if let Some(result) = evaluator.eval(&program) {
+                    println!("{} - {}", result, expected);
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_function_type_hints() {
+        let tests = vec![
+            (
+                r#"let greet = function(str name, number n) -> str { name + n.toString() }; greet("a", 1)"#,
+                Object::String("a1".to_string()),
+            ),
+            (
+                "let f = function(number n, xs) { n }; f(1.5, true)",
+                Object::Float(1.5),
+            ),
+            (
+                "let f = function(str name) { name }; f(1)",
+                Object::Error(
+                    "Type mismatch for parameter `name` of function(str name): expected str, got number"
+                        .to_string(),
+                ),
+            ),
+            (
+                "let f = function(n) -> str { if (n > 0) { return \"yes\" }; n }; f(0)",
+                Object::Error(
+                    "Type mismatch for return value of function(n) -> str: expected str, got number"
+                        .to_string(),
+                ),
+            ),
+            (
+                "let f = function(n) -> str { if (n > 0) { return \"yes\" }; n }; f(1)",
+                Object::String("yes".to_string()),
+            ),
+            (
+                "let f = function(kv h) -> bool { h.has(\"a\") }; f([])",
+                Object::Error(
+                    "Type mismatch for parameter `h` of function(kv h) -> bool: expected kv, got arr"
+                        .to_string(),
+                ),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+                if let Some(result) = evaluator.eval(&program) {
+                    println!("{} - {}", result, expected);
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_type_signature() {
+        let tests = vec![
+            ("1", "number"),
+            (r#""a""#, "str"),
+            ("len", "function"),
+            (
+                "function(str s, n) -> str { s }",
+                "function(str s, n) -> str",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program = parser.parse_program().expect("Could not parse");
+
+            let mut evaluator = Evaluator::new();
+            let result = evaluator.eval(&program).expect("No result");
+            assert_eq!(result.type_signature(), expected);
+        }
+    }
+
+    #[test]
+    fn test_null_handling() {
+        let tests = vec![
+            ("null", Object::Null),
+            ("let x = null; x is null", Object::Boolean(true)),
+            ("[1] is null", Object::Boolean(false)),
+            (
+                r#"let cfg = {"server": {"port": 80}}; cfg?.server?.port"#,
+                Object::Integer(80),
+            ),
+            (
+                r#"let cfg = {"server": {"port": 80}}; cfg.db?.host?.name"#,
+                Object::Null,
+            ),
+            (
+                r#"let cfg = {}; cfg.db?.port ?? 5432"#,
+                Object::Integer(5432),
+            ),
+            (
+                r#"let cfg = {"port": 0}; cfg.port ?? 5432"#,
+                Object::Integer(0),
+            ),
+            ("null ?? null ?? 3", Object::Integer(3)),
+            (r#""a"?.toUpper()"#, Object::String("A".to_string())),
+            // Skipped calls do not evaluate their arguments
+            ("let x = null; x?.foo(missing)", Object::Null),
+            // The right side only runs when needed
+            ("1 ?? missing", Object::Integer(1)),
+            (
+                "let x = null; x.foo",
+                Object::Error("No property named foo on Null".to_string()),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+                if let Some(result) = evaluator.eval(&program) {
+                    println!("{} - {}", result, expected);
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_const_and_block_scopes() {
+        let tests = vec![
+            ("const number n = 1; n + 1", Object::Integer(2)),
+            (
+                "const n = 1;\nn = 2",
+                Object::Error("Cannot reassign constant `n` at line 2".to_string()),
+            ),
+            (
+                "let n = 1; let n = 2",
+                Object::Error("Cannot redeclare `n` at line 1".to_string()),
+            ),
+            (
+                "const n = 1; let f = function() { n = 2 }; f()",
+                Object::Error("Cannot reassign constant `n` at line 1".to_string()),
+            ),
+            // Shadowing in an inner scope is allowed
+            (
+                "const n = 1; if (true) { let n = 2; n = 3 }; n",
+                Object::Integer(1),
+            ),
+            (
+                "let xs = []; const ys = xs; ys[0] = 1",
+                Object::Error("Index out of range: 0 (length 0)".to_string()),
+            ),
+            // Bindings end with their block
+            (
+                "if (true) { let inner = 1 }; inner",
+                Object::Error("Identifier not found (eval_identifier): inner".to_string()),
+            ),
+            // Reassignment updates the variable where it was declared
+            ("let n = 1; if (true) { n = 2 }; n", Object::Integer(2)),
+            (
+                "let count = 0; let inc = function() { count = count + 1 }; inc(); inc(); count",
+                Object::Integer(2),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+                if let Some(result) = evaluator.eval(&program) {
+                    println!("{} - {}", result, expected);
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_closures_update_captured_variables() {
+        let tests = vec![
+            (
+                r#"
+                let makeCounter = function() {
+                    let count = 0;
+                    function() { count = count + 1; count }
+                };
+                let counter = makeCounter();
+                counter();
+                counter();
+                counter()
+                "#,
+                Object::Integer(3),
+            ),
+            // Each call of the factory captures a variable of its own
+            (
+                r#"
+                let makeCounter = function() {
+                    let count = 0;
+                    function() { count = count + 1; count }
+                };
+                let a = makeCounter();
+                let b = makeCounter();
+                a();
+                a();
+                b()
+                "#,
+                Object::Integer(1),
+            ),
+            // Two closures over the same variable see each other's changes
+            (
+                r#"
+                let total = 0;
+                let add = function(n) { total = total + n };
+                let reset = function() { total = 0 };
+                add(5);
+                reset();
+                add(2);
+                total
+                "#,
+                Object::Integer(2),
+            ),
+            (
+                r#"
+                let calls = 0;
+                let memo = {};
+                let square = function(n) {
+                    if (memo.has(n)) {
+                        return memo[n];
+                    }
+                    calls = calls + 1;
+                    memo[n] = n * n;
+                    memo[n]
+                };
+                square(4);
+                square(4);
+                square(3);
+                [square(4), calls]
+                "#,
+                Object::array(vec![Object::Integer(16), Object::Integer(2)]),
+            ),
+            // A nested block updates the variable of the function around it
+            (
+                r#"
+                let last = function(xs) {
+                    let found = 0;
+                    if (len(xs) > 0) { found = xs[-1] };
+                    found
+                };
+                last([1, 2])
+                "#,
+                Object::Integer(2),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+                if let Some(result) = evaluator.eval(&program) {
+                    println!("{} - {}", result, expected);
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_type_tests_and_conversions() {
+        let tests = vec![
+            ("1 is number", Object::Boolean(true)),
+            ("1.5 is number", Object::Boolean(true)),
+            ("'a' is str", Object::Boolean(true)),
+            ("[] is arr", Object::Boolean(true)),
+            ("{} is kv", Object::Boolean(true)),
+            ("false is bool", Object::Boolean(true)),
+            ("null is str", Object::Boolean(false)),
+            ("[] is kv", Object::Boolean(false)),
+            ("type(1)", Object::String("number".to_string())),
+            ("type('a')", Object::String("str".to_string())),
+            ("type({})", Object::String("kv".to_string())),
+            ("type(null)", Object::String("null".to_string())),
+            ("type(len)", Object::String("function".to_string())),
+            (
+                "type(function() { 1 })",
+                Object::String("function".to_string()),
+            ),
+            ("int(' 42 ') + 1", Object::Integer(43)),
+            ("int(-3.9)", Object::Integer(-3)),
+            ("int(true)", Object::Integer(1)),
+            ("float('2.5')", Object::Float(2.5)),
+            ("float(2)", Object::Float(2.0)),
+            ("str(12) + '!'", Object::String("12!".to_string())),
+            ("str([1, 'a'])", Object::String("[1, a]".to_string())),
+            ("str(null)", Object::String("null".to_string())),
+            ("bool('false')", Object::Boolean(false)),
+            ("bool(2)", Object::Boolean(true)),
+            (
+                "int('4.5')",
+                Object::Error(r#"Cannot convert "4.5" to int"#.to_string()),
+            ),
+            (
+                "float('inf')",
+                Object::Error(r#"Cannot convert "inf" to float"#.to_string()),
+            ),
+            (
+                "bool('yes')",
+                Object::Error(r#"Cannot convert "yes" to bool"#.to_string()),
+            ),
+            (
+                "int([1])",
+                Object::Error("Cannot convert arr to int".to_string()),
+            ),
+            (
+                "str(1, 2)",
+                Object::Error("Wrong number of arguments. Got 2, expected 1".to_string()),
+            ),
+            // `str` and `bool` are still type hints
+            ("let str s = str(1); s is str", Object::Boolean(true)),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+                if let Some(result) = evaluator.eval(&program) {
+                    println!("{} - {}", result, expected);
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_match_expression() {
+        let describe = r#"
+            let describe = function(x) {
+                return match x do
+                    0 -> "zero"
+                    1 to 9 -> "small",
+                    -1 -> "minus one"
+                    "hi" -> "greeting"
+                    null -> "nothing",
+                    [] -> "empty",
+                    [first, ...rest] -> first + len(rest)
+                    {"code": 0, out} -> "ok " + out
+                    n if n > 100 -> "big"
+                    _ -> "other"
+                end
+            }
+        "#;
+
+        let tests = vec![
+            ("describe(0)", Object::String("zero".to_string())),
+            ("describe(2.5)", Object::String("small".to_string())),
+            ("describe(-1)", Object::String("minus one".to_string())),
+            ("describe('hi')", Object::String("greeting".to_string())),
+            ("describe(null)", Object::String("nothing".to_string())),
+            ("describe([])", Object::String("empty".to_string())),
+            ("describe([10, 20, 30])", Object::Integer(12)),
+            (
+                r#"describe({"code": 0, "out": "done"})"#,
+                Object::String("ok done".to_string()),
+            ),
+            ("describe(500)", Object::String("big".to_string())),
+            ("describe(50)", Object::String("other".to_string())),
+        ];
+
+        for (input, expected) in tests {
+            let input = format!("{}\n{}", describe, input);
+            let mut l = Lexer::new(input);
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+                if let Some(result) = evaluator.eval(&program) {
+                    println!("{} - {}", result, expected);
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+
+        let tests = vec![
+            (
+                "match 3 do\n1 -> 'one'\nend",
+                Object::Error("No match arm for 3 at line 1".to_string()),
+            ),
+            // Guards count by truthiness like `if` conditions
+            ("match 0 do\nn if n -> n\n_ -> -1\nend", Object::Integer(-1)),
+            // Bindings only live in their arm
+            ("let n = 1; match 2 do n -> n end; n", Object::Integer(1)),
+            (
+                "match [1, 2] do [a] -> a, [a, b] if a > b -> a, [a, b] -> b end",
+                Object::Integer(2),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+                if let Some(result) = evaluator.eval(&program) {
+                    println!("{} - {}", result, expected);
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_destructuring() {
+        let tests = vec![
+            ("let [a, b] = [1, 2]; a + b", Object::Integer(3)),
+            (
+                "let [first, ...rest] = [1, 2, 3]; len(rest)",
+                Object::Integer(2),
+            ),
+            ("let [_, second] = [1, 2]; second", Object::Integer(2)),
+            (
+                r#"let {name, "age": years} = {"name": "Ann", "age": 3}; years"#,
+                Object::Integer(3),
+            ),
+            (
+                "const [a] = [1]; a = 2",
+                Object::Error("Cannot reassign constant `a` at line 1".to_string()),
+            ),
+            (
+                "let a = 1; let [a] = [2]",
+                Object::Error("Cannot redeclare `a` at line 1".to_string()),
+            ),
+            (
+                "let [a, b] = [1]",
+                Object::Error("Cannot destructure [1] with [a, b] at line 1".to_string()),
+            ),
+            (
+                r#"let {name} = {"age": 3}"#,
+                Object::Error(
+                    r#"Cannot destructure {age: 3} with {"name": name} at line 1"#.to_string(),
+                ),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+                if let Some(result) = evaluator.eval(&program) {
+                    println!("{} - {}", result, expected);
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_truthiness() {
+        let tests = vec![
+            ("if (0) { 1 } else { 2 }", Object::Integer(2)),
+            ("if ('') { 1 } else { 2 }", Object::Integer(2)),
+            ("if ([]) { 1 } else { 2 }", Object::Integer(2)),
+            ("if ({}) { 1 } else { 2 }", Object::Integer(2)),
+            ("if (null) { 1 } else { 2 }", Object::Integer(2)),
+            ("if (0.0) { 1 } else { 2 }", Object::Integer(2)),
+            ("if ('a') { 1 } else { 2 }", Object::Integer(1)),
+            ("if ([0]) { 1 } else { 2 }", Object::Integer(1)),
+            ("if (len) { 1 } else { 2 }", Object::Integer(1)),
+            ("!0", Object::Boolean(true)),
+            ("not 'a'", Object::Boolean(false)),
+            ("not 1 == 2", Object::Boolean(true)),
+            ("not false and false", Object::Boolean(false)),
+            // `and` and `or` give back the side that decided
+            ("'' or 'anon'", Object::String("anon".to_string())),
+            ("'ann' or 'anon'", Object::String("ann".to_string())),
+            ("1 and 2", Object::Integer(2)),
+            ("0 and 2", Object::Integer(0)),
+            ("false or true and false", Object::Boolean(false)),
+            (
+                "let calls = 0; let f = function() { calls = calls + 1; true }; false and f(); true or f(); calls",
+                Object::Integer(0),
+            ),
+            ("true and missing", Object::Error("Identifier not found (eval_identifier): missing".to_string())),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+                if let Some(result) = evaluator.eval(&program) {
+                    println!("{} - {}", result, expected);
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_strict_conditions() {
+        let tests = vec![
+            ("if (true) { 1 } else { 2 }", Object::Integer(1)),
+            ("not false or 1", Object::Boolean(true)),
+            (
+                "if (1) { 1 }",
+                Object::Error("Use if conditionals on booleans".to_string()),
+            ),
+            (
+                "!null",
+                Object::Error("Use ! prefix operator on booleans!".to_string()),
+            ),
+            (
+                "not ''",
+                Object::Error("Use not prefix operator on booleans!".to_string()),
+            ),
+            (
+                "1 and true",
+                Object::Error("Use and operator on booleans".to_string()),
+            ),
+            (
+                "false or 'a'",
+                Object::Error("Use or operator on booleans".to_string()),
+            ),
+            (
+                "match 3 do\nn if n -> n\nend",
+                Object::Error("Match guard must be a Boolean, got Integer at line 2".to_string()),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+                evaluator.set_strict(true);
+                if let Some(result) = evaluator.eval(&program) {
+                    println!("{} - {}", result, expected);
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
     #[test]
-    fn test_dot_notation() {
+    fn test_equality_and_ordering() {
         let tests = vec![
+            ("[1, 2] == [1, 2]", Object::Boolean(true)),
+            ("[1, [2, 3]] == [1, [2, 3.0]]", Object::Boolean(true)),
+            ("[1, 2] == [2, 1]", Object::Boolean(false)),
             (
-                r#"
-                let kv person = {"name": "Joe"};
-                person.name
-                "#,
-                Object::String("Joe".to_string()),
+                r#"{"a": 1, "b": [2]} == {"b": [2], "a": 1}"#,
+                Object::Boolean(true),
             ),
+            (r#"{"a": 1} != {"a": 2}"#, Object::Boolean(true)),
+            ("null == null", Object::Boolean(true)),
+            ("null == 0", Object::Boolean(false)),
+            ("1 == '1'", Object::Boolean(false)),
+            ("[] != {}", Object::Boolean(true)),
             (
-                r#"
-            let person = {"name": "Joe", "age": 90};
-            person.age
-            "#,
-                Object::Integer(90),
+                "let f = function() { 1 }; let g = f; f == g",
+                Object::Boolean(true),
+            ),
+            ("len == len", Object::Boolean(true)),
+            ("len == first", Object::Boolean(false)),
+            ("contains([[1], [2]], [2])", Object::Boolean(true)),
+            ("indexOf([1, 2.0], 2)", Object::Integer(1)),
+            ("'abc' < 'abd'", Object::Boolean(true)),
+            ("'b' > 'abc'", Object::Boolean(true)),
+            ("[1, 2] < [1, 3]", Object::Boolean(true)),
+            ("[1] < [1, 0]", Object::Boolean(true)),
+            ("[2] > [1, 9]", Object::Boolean(true)),
+            ("false < true", Object::Boolean(true)),
+            ("-0.0 < 0.0", Object::Boolean(false)),
+            ("[-0.0] < [0.0]", Object::Boolean(false)),
+            (
+                "str(sort([0.0, -0.0]))",
+                Object::String("[0.0, -0.0]".to_string()),
+            ),
+            (
+                "[0.0 / 0.0] < [1.0]",
+                Object::Error("Cannot compare NaN".to_string()),
+            ),
+            (
+                "sort([1.0, 0.0 / 0.0])",
+                Object::Error("sort: Cannot compare NaN".to_string()),
+            ),
+            (
+                "1 < 'a'",
+                Object::Error("Cannot compare Integer and String".to_string()),
+            ),
+            (
+                "[1] < ['a']",
+                Object::Error("Cannot compare Integer and String".to_string()),
+            ),
+            (
+                "{} < {}",
+                Object::Error("Cannot compare Hash and Hash".to_string()),
             ),
         ];
 
         for (input, expected) in tests {
-            // create new lexer with input
             let mut l = Lexer::new(input.to_string());
-            // generate tokens from lexer
             let tokens = l.gen_tokens();
 
-            // create new parser with tokens
             let mut parser = Parser::new(tokens.expect("Could not tokenize"));
-            // parse program from parser
             let program: Option<Program> = parser.parse_program();
 
-            // if program exists
             if let Some(program) = program {
-                // create new evaluator
                 let mut evaluator = Evaluator::new();
-                // evaluate program
                 if let Some(result) = evaluator.eval(&program) {
-                    // assert that result is equal to expected
                     println!("{} - {}", result, expected);
                     assert_eq!(result, expected);
                 } else {
@@ -54,56 +1472,94 @@ mod test {
     }
 
     #[test]
-    fn test_hash_index() {
+    fn test_strict_mode_warnings() {
+        let input = "let number a = 1;\nlet b = 2;\nlet f = function() { let c = 3; c }; f()";
+
+        let mut l = Lexer::new(input.to_string());
+        let tokens = l.gen_tokens();
+
+        let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+        let program = parser.parse_program().expect("Could not parse");
+
+        let mut evaluator = Evaluator::new();
+        evaluator.eval(&program);
+        assert!(evaluator.take_warnings().is_empty());
+
+        let mut evaluator = Evaluator::new();
+        evaluator.set_strict(true);
+        evaluator.eval(&program);
+        assert_eq!(
+            evaluator.take_warnings(),
+            vec![
+                "Missing type hint for `b` at line 2".to_string(),
+                "Missing type hint for `f` at line 3".to_string(),
+                "Missing type hint for `c` at line 3".to_string(),
+            ]
+        );
+        assert!(evaluator.take_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_collections_are_shared() {
         let tests = vec![
+            (
+                "let xs = [1, 2]; let ys = xs; ys[0] = 9; xs[0]",
+                Object::Integer(9),
+            ),
             (
                 r#"
-                let kv myHash = {"one": 1, "two": 2};
-                myHash["one"]
+                let bump = function(h) { h.count = h.count + 1 };
+                let counter = {"count": 0};
+                bump(counter);
+                bump(counter);
+                counter.count
                 "#,
-                Object::Integer(1),
+                Object::Integer(2),
             ),
             (
                 r#"
-                let kv myHash = {"one": 1, "two": 2};
-                myHash["two"]
+                let inner = [1];
+                let outer = {"items": inner};
+                outer.items[0] = 5;
+                inner[0]
                 "#,
-                Object::Integer(2),
+                Object::Integer(5),
+            ),
+            (
+                "let xs = [1, 2]; let ys = copy(xs); ys[0] = 9; xs[0]",
+                Object::Integer(1),
             ),
             (
                 r#"
-                let kv myHash = {"one": 1, "two": 2};
-                myHash["three"]
+                let cfg = {"server": {"port": 80}};
+                let shallow = copy(cfg);
+                shallow.server.port = 81;
+                cfg.server.port
                 "#,
-                Object::Null,
+                Object::Integer(81),
             ),
             (
                 r#"
-                let kv myHash = {"one": 1, "two": 2};
-                myHash["one"] + myHash["two"]
+                let cfg = {"server": {"port": 80}};
+                let deep = deepCopy(cfg);
+                deep.server.port = 81;
+                cfg.server.port
                 "#,
-                Object::Integer(3),
+                Object::Integer(80),
             ),
+            (r#"deepCopy("text")"#, Object::String("text".to_string())),
         ];
 
         for (input, expected) in tests {
-            // create new lexer with input
             let mut l = Lexer::new(input.to_string());
-            // generate tokens from lexer
             let tokens = l.gen_tokens();
 
-            // create new parser with tokens
             let mut parser = Parser::new(tokens.expect("Could not tokenize"));
-            // parse program from parser
             let program: Option<Program> = parser.parse_program();
 
-            // if program exists
             if let Some(program) = program {
-                // create new evaluator
                 let mut evaluator = Evaluator::new();
-                // evaluate program
                 if let Some(result) = evaluator.eval(&program) {
-                    // assert that result is equal to expected
                     println!("{} - {}", result, expected);
                     assert_eq!(result, expected);
                 } else {
@@ -113,6 +1569,76 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_collections_containing_themselves() {
+        let cycle = "let a = [1]; a[0] = a; let b = [1]; b[0] = b;";
+        let tests = vec![
+            (
+                format!("{} deepCopy(a)", cycle),
+                Object::Error("Cannot copy a value that contains itself".to_string()),
+            ),
+            (format!("{} a == a", cycle), Object::Boolean(true)),
+            (
+                format!("{} a == b", cycle),
+                Object::Error("Cannot compare a value that contains itself".to_string()),
+            ),
+            (
+                format!("{} [a] < [b]", cycle),
+                Object::Error("Cannot compare a value that contains itself".to_string()),
+            ),
+            (
+                format!("{} contains([a], b)", cycle),
+                Object::Error("contains: Cannot compare a value that contains itself".to_string()),
+            ),
+            (
+                format!("{} unique([a, b])", cycle),
+                Object::Error("unique: Cannot compare a value that contains itself".to_string()),
+            ),
+            (
+                format!("{} flatten(a, 1000000)", cycle),
+                Object::Error("flatten: Cannot flatten a value that contains itself".to_string()),
+            ),
+            (
+                r#"let h = {"name": "h"}; h["self"] = h; json.stringify(h)"#.to_string(),
+                Object::Error("Cannot serialize a value that contains itself to JSON".to_string()),
+            ),
+            (
+                r#"let h = {"server": {}}; h.server["root"] = h; toml.stringify(h)"#.to_string(),
+                Object::Error("Cannot serialize a value that contains itself to TOML".to_string()),
+            ),
+            (
+                format!(r#"{} toml.stringify({{"a": a}})"#, cycle),
+                Object::Error("Cannot serialize a value that contains itself to TOML".to_string()),
+            ),
+            // Sharing without a cycle is fine
+            (
+                "let x = [1]; json.stringify([x, x])".to_string(),
+                Object::String("[[1],[1]]".to_string()),
+            ),
+            (
+                "let x = [1]; deepCopy([x, x]) == [[1], [1]]".to_string(),
+                Object::Boolean(true),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input);
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+                if let Some(result) = evaluator.eval(&program) {
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_hash_literal() {
         let tests = vec![(
@@ -123,8 +1649,8 @@ mod test {
                 }
                 "#,
             vec![
-                (Object::String("one".to_string()), Object::Integer(1)),
-                (Object::String("three".to_string()), Object::Integer(3)),
+                (HashKey::from("one"), Object::Integer(1)),
+                (HashKey::from("three"), Object::Integer(3)),
             ],
         )];
 
@@ -141,7 +1667,7 @@ mod test {
                 if let Some(result) = evaluator.eval(&program) {
                     match result {
                         Object::Hash(hash) => {
-                            for (key, value) in hash.iter() {
+                            for (key, value) in hash.borrow().iter() {
                                 for (expected_key, expected_value) in object.iter() {
                                     if key == expected_key {
                                         assert_eq!(value, expected_value);
@@ -166,56 +1692,301 @@ mod test {
             ("[1, 2, 3][1 + 1];", Object::Integer(3)),
             ("let myArray = [1, 2, 3]; myArray[2];", Object::Integer(3)),
             (
-                "let myArray = [1, 2, 3]; myArray[0] + myArray[1] + myArray[2];",
-                Object::Integer(6),
+                "let myArray = [1, 2, 3]; myArray[0] + myArray[1] + myArray[2];",
+                Object::Integer(6),
+            ),
+            (
+                "let myArray = [1, 2, 3]; let i = myArray[0]; myArray[i]",
+                Object::Integer(2),
+            ),
+            ("[1, 2, 3][3]", Object::Null),
+            ("[1, 2, 3][-1]", Object::Integer(3)),
+        ];
+
+        for (input, expected) in tests {
+            // create new lexer with input
+            let mut l = Lexer::new(input.to_string());
+            // generate tokens from lexer
+            let tokens = l.gen_tokens();
+
+            // create new parser with tokens
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            // parse program from parser
+            let program: Option<Program> = parser.parse_program();
+
+            // if program exists
+            if let Some(program) = program {
+                // create new evaluator
+                let mut evaluator = Evaluator::new();
+                // evaluate program
+                if let Some(result) = evaluator.eval(&program) {
+                    // assert that result is equal to expected
+                    assert_eq!(result, expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_slice_expression() {
+        let ints = |items: &[i64]| {
+            Object::array(items.iter().map(|item| Object::Integer(*item)).collect())
+        };
+
+        let tests = vec![
+            ("[0, 1, 2, 3, 4][1:3]", ints(&[1, 2])),
+            ("[0, 1, 2, 3, 4][-2:]", ints(&[3, 4])),
+            ("[0, 1, 2, 3, 4][:2]", ints(&[0, 1])),
+            ("[0, 1, 2, 3, 4][::2]", ints(&[0, 2, 4])),
+            ("[0, 1, 2, 3, 4][::-1]", ints(&[4, 3, 2, 1, 0])),
+            ("[0, 1, 2, 3, 4][3:0:-2]", ints(&[3, 1])),
+            ("[0, 1, 2, 3, 4][2:100]", ints(&[2, 3, 4])),
+            ("[0, 1, 2, 3, 4][3:1]", ints(&[])),
+            ("[1, 2, 3][2::9223372036854775807]", ints(&[3])),
+            ("[1, 2, 3][::-9223372036854775807]", ints(&[3])),
+            (
+                r#""abc"[1::9223372036854775807]"#,
+                Object::String("b".to_string()),
+            ),
+            (
+                "let xs = [1, 2]; let ys = xs[:]; ys[0] = 9; xs[0]",
+                Object::Integer(1),
+            ),
+            (r#""hello"[1:3]"#, Object::String("el".to_string())),
+            (r#""héllo"[-3:]"#, Object::String("llo".to_string())),
+            (r#""hello"[::-1]"#, Object::String("olleh".to_string())),
+            (
+                "[1, 2][::0]",
+                Object::Error("Slice step must not be zero".to_string()),
+            ),
+            (
+                r#"[1, 2]["a":]"#,
+                Object::Error("Slice bounds must be Integers, got String".to_string()),
+            ),
+            (
+                "let n = 5; n[1:]",
+                Object::Error("Use slices on arrays or strings, got Integer".to_string()),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+                if let Some(result) = evaluator.eval(&program) {
+                    println!("{} - {}", result, expected);
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_array_literal() {
+        let test = vec![
+            (
+                "[1, 2 * 2, 3 + 3]",
+                vec![Object::Integer(1), Object::Integer(4), Object::Integer(6)],
+            ),
+            (
+                "[1, 2, 3]",
+                vec![Object::Integer(1), Object::Integer(2), Object::Integer(3)],
+            ),
+            ("[]", vec![]),
+        ];
+
+        for (input, expected) in test {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+
+                if let Some(result) = evaluator.eval(&program) {
+                    match result {
+                        Object::Array(arr) => {
+                            for (i, obj) in arr.borrow().iter().enumerate() {
+                                assert_eq!(*obj, expected[i]);
+                            }
+                        }
+                        _ => panic!("Expected array, got {}", result),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_array_builtins() {
+        let ints = |values: &[i64]| {
+            Object::array(values.iter().map(|value| Object::Integer(*value)).collect())
+        };
+
+        let tests = vec![
+            ("last([1, 2, 3])", Object::Integer(3)),
+            ("rest([1, 2, 3])", ints(&[2, 3])),
+            ("rest([])", Object::Null),
+            ("slice([1, 2, 3, 4], 1, 3)", ints(&[2, 3])),
+            ("slice([1, 2, 3, 4], -2)", ints(&[3, 4])),
+            ("slice([1, 2, 3], 5)", ints(&[])),
+            (r#"slice("hello", 1, 3)"#, Object::String("el".to_string())),
+            ("concat([1], [2, 3], [])", ints(&[1, 2, 3])),
+            ("reverse([1, 2, 3])", ints(&[3, 2, 1])),
+            ("contains([1, 2, 3], 2)", Object::Boolean(true)),
+            (r#"contains([1, 2, 3], "2")"#, Object::Boolean(false)),
+            ("indexOf([1, 2, 3], 3)", Object::Integer(2)),
+            ("indexOf([1, 2, 3], 4)", Object::Integer(-1)),
+            (
+                r#"join([1, "a", true], ", ")"#,
+                Object::String("1, a, true".to_string()),
+            ),
+            (
+                "map([1, 2, 3], function(x) { x * 10 })",
+                ints(&[10, 20, 30]),
+            ),
+            (r#"map(["a", "bb"], len)"#, ints(&[1, 2])),
+            ("filter(range(6), function(x) { x > 3 })", ints(&[4, 5])),
+            (
+                "reduce([1, 2, 3], function(acc, x) { acc + x })",
+                Object::Integer(6),
+            ),
+            (
+                "reduce([], function(acc, x) { acc + x }, 10)",
+                Object::Integer(10),
+            ),
+            ("find([1, 2, 3], function(x) { x > 1 })", Object::Integer(2)),
+            ("find([1, 2, 3], function(x) { x > 5 })", Object::Null),
+            (
+                "any([1, 2, 3], function(x) { x == 2 })",
+                Object::Boolean(true),
+            ),
+            (
+                "all([1, 2, 3], function(x) { x > 1 })",
+                Object::Boolean(false),
             ),
             (
-                "let myArray = [1, 2, 3]; let i = myArray[0]; myArray[i]",
-                Object::Integer(2),
+                "sort([3, 1.5, 2])",
+                Object::array(vec![
+                    Object::Float(1.5),
+                    Object::Integer(2),
+                    Object::Integer(3),
+                ]),
+            ),
+            (
+                "[[2], [1, 5], [1]].sort()",
+                Object::array(vec![ints(&[1]), ints(&[1, 5]), ints(&[2])]),
+            ),
+            ("sortBy([3, 1, 2], function(x) { x })", ints(&[1, 2, 3])),
+            ("sortBy([3, 1, 2], function(x) { 0 - x })", ints(&[3, 2, 1])),
+            (
+                r#"sortBy(["bb", "a", "ccc"], function(s) { len(s) })"#,
+                Object::array(vec![
+                    Object::String("a".to_string()),
+                    Object::String("bb".to_string()),
+                    Object::String("ccc".to_string()),
+                ]),
+            ),
+            (
+                "zip([1, 2, 3], [4, 5])",
+                Object::array(vec![ints(&[1, 4]), ints(&[2, 5])]),
+            ),
+            (
+                "flatten([1, [2, [3]]])",
+                Object::array(vec![Object::Integer(1), Object::Integer(2), ints(&[3])]),
+            ),
+            ("flatten([1, [2, [3]]], 2)", ints(&[1, 2, 3])),
+            ("unique([1, 2, 1, 3, 2])", ints(&[1, 2, 3])),
+            ("unique([1, 1.0, 2])", ints(&[1, 2])),
+            (
+                "unique([[1], [1], \"a\", \"a\"])",
+                Object::array(vec![ints(&[1]), Object::String("a".to_string())]),
+            ),
+            ("range(3)", ints(&[0, 1, 2])),
+            ("range(1, 4)", ints(&[1, 2, 3])),
+            ("range(5, 0, -2)", ints(&[5, 3, 1])),
+            (
+                "range(0, 5, 0)",
+                Object::Error("Step for `range` must not be zero".to_string()),
+            ),
+            (
+                "filter([1, 2], function(x) { x })",
+                Object::Error(
+                    "Callback to `filter` must return a Boolean, got Integer at line 1".to_string(),
+                ),
+            ),
+            (
+                "map([1, 2], function(x) { x / 0 })",
+                Object::Error("Division by zero".to_string()),
+            ),
+            (
+                r#"sort([1, "a"])"#,
+                Object::Error("sort: Cannot compare String and Integer".to_string()),
+            ),
+            (
+                r#"sortBy([1, "a"], function(x) { x })"#,
+                Object::Error("sortBy: Cannot compare String and Integer".to_string()),
+            ),
+            (
+                "reduce([], function(acc, x) { acc + x })",
+                Object::Error("`reduce` of an empty array needs an initial value".to_string()),
             ),
-            ("[1, 2, 3][3]", Object::Null),
-            ("[1, 2, 3][-1]", Object::Integer(3)),
         ];
 
         for (input, expected) in tests {
-            // create new lexer with input
             let mut l = Lexer::new(input.to_string());
-            // generate tokens from lexer
             let tokens = l.gen_tokens();
 
-            // create new parser with tokens
             let mut parser = Parser::new(tokens.expect("Could not tokenize"));
-            // parse program from parser
             let program: Option<Program> = parser.parse_program();
 
-            // if program exists
             if let Some(program) = program {
-                // create new evaluator
                 let mut evaluator = Evaluator::new();
-                // evaluate program
                 if let Some(result) = evaluator.eval(&program) {
-                    // assert that result is equal to expected
+                    println!("{} - {}", result, expected);
                     assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
                 }
             }
         }
     }
 
     #[test]
-    fn test_array_literal() {
-        let test = vec![
+    fn test_define_builtin() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+        use tammr::builtin::NativeModule;
+
+        let tests = vec![
+            ("counter(); counter(); counter()", Object::Integer(3)),
+            ("let base = 40; lookup(\"base\")", Object::Integer(40)),
+            ("twice(function(x) { x * 2 }, 5)", Object::Integer(20)),
             (
-                "[1, 2 * 2, 3 + 3]",
-                vec![Object::Integer(1), Object::Integer(4), Object::Integer(6)],
+                "twice(len, \"abc\")",
+                Object::Error("Argument to `len` not supported, got Integer".to_string()),
             ),
             (
-                "[1, 2, 3]",
-                vec![Object::Integer(1), Object::Integer(2), Object::Integer(3)],
+                "greet.hello(\"Tammr\")",
+                Object::String("Hello, Tammr".to_string()),
+            ),
+            ("greet.version", Object::Integer(1)),
+            (
+                "lookup(1)",
+                Object::Error("`lookup` needs a variable name at line 1".to_string()),
             ),
-            ("[]", vec![]),
         ];
 
-        for (input, expected) in test {
+        for (input, expected) in tests {
             let mut l = Lexer::new(input.to_string());
             let tokens = l.gen_tokens();
 
@@ -225,15 +1996,32 @@ mod test {
             if let Some(program) = program {
                 let mut evaluator = Evaluator::new();
 
+                let count = Rc::new(Cell::new(0));
+                evaluator.define_builtin("counter", move |_, _| {
+                    count.set(count.get() + 1);
+                    Object::Integer(count.get())
+                });
+                evaluator.define_builtin("lookup", |ctx, args| match &args[0] {
+                    Object::String(name) => ctx.get_var(name).unwrap_or(Object::Null),
+                    _ => ctx.error(&format!("`{}` needs a variable name", ctx.name())),
+                });
+                evaluator.define_builtin("twice", |ctx, args| {
+                    let once = ctx.call(&args[0], vec![args[1].clone()]);
+                    ctx.call(&args[0], vec![once])
+                });
+                evaluator.define_module(
+                    NativeModule::new("greet")
+                        .function("hello", |_, args| {
+                            Object::String(format!("Hello, {}", args[0]))
+                        })
+                        .value("version", Object::Integer(1)),
+                );
+
                 if let Some(result) = evaluator.eval(&program) {
-                    match result {
-                        Object::Array(arr) => {
-                            for (i, obj) in arr.iter().enumerate() {
-                                assert_eq!(*obj, expected[i]);
-                            }
-                        }
-                        _ => panic!("Expected array, got {}", result),
-                    }
+                    println!("{} - {}", result, expected);
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
                 }
             }
         }
@@ -245,6 +2033,7 @@ mod test {
             ("len(\"\")", Object::Integer(0)),
             ("len(\"four\")", Object::Integer(4)),
             ("len(\"hello world\")", Object::Integer(11)),
+            ("len(\"héllo wörld\")", Object::Integer(11)),
             (
                 "len(1)",
                 Object::Error("Argument to `len` not supported, got Integer".to_string()),
@@ -335,6 +2124,303 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_string_methods() {
+        let strings = |items: &[&str]| {
+            Object::array(
+                items
+                    .iter()
+                    .map(|item| Object::String(item.to_string()))
+                    .collect(),
+            )
+        };
+
+        let tests = vec![
+            (r#""a,b,,c".split(",")"#, strings(&["a", "b", "", "c"])),
+            (r#""  one two ".split()"#, strings(&["one", "two"])),
+            (r#""abc".split("")"#, strings(&["a", "b", "c"])),
+            (
+                r#""a-b-c".replace("-", "+")"#,
+                Object::String("a+b+c".to_string()),
+            ),
+            (r#""  hi  ".trim()"#, Object::String("hi".to_string())),
+            (
+                r#""  hi  ".trimStart()"#,
+                Object::String("hi  ".to_string()),
+            ),
+            (r#""  hi  ".trimEnd()"#, Object::String("  hi".to_string())),
+            (r#""Hi".toUpper()"#, Object::String("HI".to_string())),
+            (r#""Hi".toLower()"#, Object::String("hi".to_string())),
+            (r#""hello".contains("ell")"#, Object::Boolean(true)),
+            (r#""hello".startsWith("he")"#, Object::Boolean(true)),
+            (r#""hello".endsWith("he")"#, Object::Boolean(false)),
+            (r#""héllo".indexOf("llo")"#, Object::Integer(2)),
+            (r#""hello".indexOf("x")"#, Object::Integer(-1)),
+            (r#""ab".repeat(3)"#, Object::String("ababab".to_string())),
+            (r#""7".padStart(3, "0")"#, Object::String("007".to_string())),
+            (r#""7".padStart(3)"#, Object::String("  7".to_string())),
+            (r#""long".padStart(2)"#, Object::String("long".to_string())),
+            (r#""a\nb\n".lines()"#, strings(&["a", "b"])),
+            (
+                r#""hello".substring(1, 3)"#,
+                Object::String("el".to_string()),
+            ),
+            (
+                r#""hello".substring(-3)"#,
+                Object::String("llo".to_string()),
+            ),
+            (r#""héllo".length"#, Object::Integer(5)),
+            (r#""héllo"[1]"#, Object::String("é".to_string())),
+            (r#""héllo"[-4]"#, Object::String("é".to_string())),
+            (r#""héllo"[5]"#, Object::Null),
+            (r#""héllo"[-6]"#, Object::Null),
+            (
+                r#""hé".bytes"#,
+                Object::array(vec![
+                    Object::Integer(104),
+                    Object::Integer(195),
+                    Object::Integer(169),
+                ]),
+            ),
+            (
+                r#""hé".codepoints"#,
+                Object::array(vec![Object::Integer(104), Object::Integer(233)]),
+            ),
+            (r#""".is_capitalized"#, Object::Boolean(false)),
+            (r#""Émile".is_capitalized"#, Object::Boolean(true)),
+            (r#""Hello World".is_titlecase"#, Object::Boolean(true)),
+            (r#""Tammr-Script 2".is_titlecase"#, Object::Boolean(true)),
+            (r#""Hello world".is_titlecase"#, Object::Boolean(false)),
+            (r#""HELLO".is_titlecase"#, Object::Boolean(false)),
+            (r#""".is_titlecase"#, Object::Boolean(false)),
+            (
+                r#"let s = " Key=Value "; s.trim().split("=")[1].toLower()"#,
+                Object::String("value".to_string()),
+            ),
+            (
+                r#""hello".shout()"#,
+                Object::Error("No method named shout on String".to_string()),
+            ),
+            (
+                r#""hello".trim(1)"#,
+                Object::Error("Wrong number of arguments. Got 1, expected 0".to_string()),
+            ),
+            (
+                r#""hello".contains(1)"#,
+                Object::Error("Argument to `contains` must be STRING, got 1".to_string()),
+            ),
+            (
+                r#""hello".repeat(-1)"#,
+                Object::Error("Count for `repeat` must not be negative, got -1".to_string()),
+            ),
+            (
+                r#""ab".repeat(9223372036854775807)"#,
+                Object::Error(
+                    "Result of `repeat` would be longer than 1073741824 bytes".to_string(),
+                ),
+            ),
+            (
+                r#""ab".padStart(9223372036854775807)"#,
+                Object::Error(
+                    "Result of `padStart` would be longer than 1073741824 bytes".to_string(),
+                ),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+                if let Some(result) = evaluator.eval(&program) {
+                    println!("{} - {}", result, expected);
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_method_calls() {
+        let tests = vec![
+            ("[1, 2, 3].length", Object::Integer(3)),
+            (
+                "[1, 2, 3].map(function(x) { x * 2 }).reduce(function(a, b) { a + b }, 0)",
+                Object::Integer(12),
+            ),
+            (
+                "let xs = [3, 1, 2]; xs.sortBy(function(x) { x }).join(\",\")",
+                Object::String("1,2,3".to_string()),
+            ),
+            (
+                "[1, 2].filter(function(x) { x })",
+                Object::Error(
+                    "Callback to `filter` must return a Boolean, got Integer at line 1".to_string(),
+                ),
+            ),
+            (
+                r#"let h = {"a": 1, "b": 2}; h.keys()"#,
+                Object::array(vec![
+                    Object::String("a".to_string()),
+                    Object::String("b".to_string()),
+                ]),
+            ),
+            (
+                r#"{"a": 1, "b": 2}.values()"#,
+                Object::array(vec![Object::Integer(1), Object::Integer(2)]),
+            ),
+            (r#"{"a": 1}.has("a")"#, Object::Boolean(true)),
+            (r#"{"a": 1}.has("b")"#, Object::Boolean(false)),
+            (
+                r#"let h = {"a": 1, "b": 2}; h.remove("a"); h.keys()"#,
+                Object::array(vec![Object::String("b".to_string())]),
+            ),
+            (r#"{"a": 1}.remove("b")"#, Object::Null),
+            (
+                r#"let h = {"keys": function() { "own" }}; h.keys()"#,
+                Object::String("own".to_string()),
+            ),
+            ("let n = -5; n.abs()", Object::Integer(5)),
+            ("let n = 42; n.toString()", Object::String("42".to_string())),
+            ("let n = 21; n.double()", Object::Integer(42)),
+            ("let f = -1.5; f.abs()", Object::Float(1.5)),
+            (
+                r#""abc".length.toString()"#,
+                Object::String("3".to_string()),
+            ),
+            (
+                "let n = 1; n.shout()",
+                Object::Error("No method named shout on Integer".to_string()),
+            ),
+            (
+                "[1].size",
+                Object::Error("No property named size on Array".to_string()),
+            ),
+            (
+                r#"{"a": 1}.has([1])"#,
+                Object::Error("Unusable as hash key: Array".to_string()),
+            ),
+            (
+                "let n = 1; n.abs(2)",
+                Object::Error("Wrong number of arguments. Got 1, expected 0".to_string()),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+                evaluator.define_method("Integer", "double", |_, args| match &args[0] {
+                    Object::Integer(int) => Object::Integer(int * 2),
+                    _ => Object::Null,
+                });
+
+                if let Some(result) = evaluator.eval(&program) {
+                    println!("{} - {}", result, expected);
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_regex() {
+        let strings = |items: &[&str]| {
+            Object::array(
+                items
+                    .iter()
+                    .map(|item| Object::String(item.to_string()))
+                    .collect(),
+            )
+        };
+
+        let tests = vec![
+            (r#"/^\d+$/.test("123")"#, Object::Boolean(true)),
+            (r#"/^\d+$/.test("12a")"#, Object::Boolean(false)),
+            (r#"/hello/i.test("HeLLo")"#, Object::Boolean(true)),
+            (r#"regex("a+").test("baa")"#, Object::Boolean(true)),
+            (
+                r#"/\d+/.match("port 8080")"#,
+                Object::String("8080".to_string()),
+            ),
+            (r#"/\d+/.match("none")"#, Object::Null),
+            (
+                r#"/(?P<key>\w+)=(?P<value>\d*)/.captures("x: port=80").value"#,
+                Object::String("80".to_string()),
+            ),
+            (
+                r#"/(\w+)@(\w+)/.captures("me@host")[2]"#,
+                Object::String("host".to_string()),
+            ),
+            (r#"/(a)|(b)/.captures("b")[1]"#, Object::Null),
+            (r#"/\d+/.findAll("a1b22c")"#, strings(&["1", "22"])),
+            (r#"len(/(\w)=(\d)/.findAll("a=1 b=2"))"#, Object::Integer(2)),
+            (
+                r#"/\s+/.replace("a  b   c", " ")"#,
+                Object::String("a b c".to_string()),
+            ),
+            (
+                r#"/(?P<k>\w+)=(?P<v>\w+)/.replace("a=1", "$v=$k")"#,
+                Object::String("1=a".to_string()),
+            ),
+            (
+                r#"/\d+/.replace("a1b22", function(m) { "<" + m[0] + ">" })"#,
+                Object::String("a<1>b<22>".to_string()),
+            ),
+            (r#"/,\s*/.split("a, b,c")"#, strings(&["a", "b", "c"])),
+            (r"/a\/b/.pattern", Object::String("a/b".to_string())),
+            ("let n = 10; n / 2 / 5", Object::Integer(1)),
+            (
+                r#"regex("(")"#,
+                Object::Error(
+                    "Invalid regex `(`: regex parse error:\n    (\n    ^\nerror: unclosed group"
+                        .to_string(),
+                ),
+            ),
+            (
+                r#"/x/.replace("x", function(m) { 1 })"#,
+                Object::Error(
+                    "Callback to `replace` must return a String, got Integer at line 1".to_string(),
+                ),
+            ),
+            (
+                "/x/.test(1)",
+                Object::Error("Argument to `test` must be STRING, got 1".to_string()),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+                if let Some(result) = evaluator.eval(&program) {
+                    println!("{} - {}", result, expected);
+                    assert_eq!(result, expected);
+                } else {
+                    panic!("No result");
+                }
+            }
+        }
+    }
+
     #[test]
     fn function_call_test() {
         let tests = vec![
@@ -560,6 +2646,77 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_integer_overflow() {
+        let min = "let min = -9223372036854775807 - 1;";
+        let tests = vec![
+            (
+                "9223372036854775807 + 1".to_string(),
+                "Integer overflow: 9223372036854775807 + 1",
+            ),
+            (
+                format!("{} min - 1", min),
+                "Integer overflow: -9223372036854775808 - 1",
+            ),
+            (
+                "4611686018427387904 * 2".to_string(),
+                "Integer overflow: 4611686018427387904 * 2",
+            ),
+            (
+                format!("{} min / -1", min),
+                "Integer overflow: -9223372036854775808 / -1",
+            ),
+            (
+                format!("{} -min", min),
+                "Integer overflow: -(-9223372036854775808)",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input);
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+
+                if let Some(result) = evaluator.eval(&program) {
+                    assert_eq!(result, Object::Error(expected.to_string()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_float_arithmetic() {
+        let tests = vec![
+            ("1.5 + 1", Object::Float(2.5)),
+            ("3 / 2.0", Object::Float(1.5)),
+            ("-2.5", Object::Float(-2.5)),
+            ("0.5 * 0.5", Object::Float(0.25)),
+            ("0.1 < 0.2", Object::Boolean(true)),
+            ("2.0 == 2", Object::Boolean(true)),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut parser = Parser::new(tokens.expect("Could not tokenize"));
+            let program: Option<Program> = parser.parse_program();
+
+            if let Some(program) = program {
+                let mut evaluator = Evaluator::new();
+
+                if let Some(result) = evaluator.eval(&program) {
+                    assert_eq!(result, expected);
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_int_minus_prefix() {
         let tests = vec![
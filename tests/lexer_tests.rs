@@ -183,12 +183,65 @@ mod lexer_test {
         }
     }
 
+    #[test]
+    fn test_regex_and_division() {
+        use tammr::lexer::{Lexer, TokenType};
+
+        let input = String::from(r"let re = /\d+\/x/i; a / b / 2; f(/x/)");
+        let mut l = Lexer::new(input);
+        let tokens = l.gen_tokens().expect("Could not tokenize!");
+
+        assert_eq!(tokens[3].ttype, TokenType::Regex);
+        assert_eq!(tokens[3].literal, String::from(r"(?i)\d+/x"));
+
+        let divisions = tokens
+            .iter()
+            .filter(|token| token.ttype == TokenType::Div)
+            .count();
+        assert_eq!(divisions, 2);
+
+        assert_eq!(tokens[tokens.len() - 3].ttype, TokenType::Regex);
+        assert_eq!(tokens[tokens.len() - 3].literal, String::from("x"));
+
+        let mut l = Lexer::new(String::from("let re = /abc"));
+        assert!(l.gen_tokens().is_err());
+    }
+
+    #[test]
+    fn test_null_operators() {
+        use tammr::lexer::{Lexer, TokenType};
+
+        let input = String::from("a?.b ?? c -> d ..._x");
+        let mut l = Lexer::new(input);
+        let tokens = l.gen_tokens().expect("Could not tokenize!");
+
+        let types: Vec<TokenType> = tokens.iter().map(|token| token.ttype.clone()).collect();
+        assert_eq!(
+            types,
+            vec![
+                TokenType::Ident,
+                TokenType::QuestionDot,
+                TokenType::Ident,
+                TokenType::Coalesce,
+                TokenType::Ident,
+                TokenType::Arrow,
+                TokenType::Ident,
+                TokenType::Ellipsis,
+                TokenType::Ident,
+                TokenType::Eof,
+            ]
+        );
+
+        let mut l = Lexer::new(String::from("a ? b"));
+        assert!(l.gen_tokens().is_err());
+    }
+
     #[test]
     fn test_keywords() {
         use tammr::lexer::{KeywordType, Lexer, TokenType};
 
         let input = String::from(
-        "let function return if else do end loop exit true false null try catch throw and or not is"
+        "let function return if else do end loop exit true false null try catch throw and or not is match"
     );
         let mut l = Lexer::new(input);
         let tokens = l.gen_tokens().expect("Could not tokenize!");
@@ -213,6 +266,7 @@ mod lexer_test {
             (KeywordType::Or, "or"),
             (KeywordType::Not, "not"),
             (KeywordType::Is, "is"),
+            (KeywordType::Match, "match"),
         ];
 
         assert_eq!(tokens.len(), expected_keywords.len() + 1); // +1 for EOF token
@@ -104,6 +104,244 @@ mod parser_tests {
         }
     }
 
+    #[test]
+    fn test_slice_expression() {
+        let tests = vec![
+            ("xs[1:3]", "(xs[1:3])"),
+            ("xs[-3:]", "(xs[(-3):])"),
+            ("xs[:n + 1]", "(xs[:(n + 1)])"),
+            ("xs[:]", "(xs[:])"),
+            ("xs[::-1]", "(xs[::(-1)])"),
+            ("xs[1:10:2][0]", "((xs[1:10:2])[0])"),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut p = Parser::new(tokens.expect("Could not tokenize"));
+            let program = p.parse_program();
+            if let Some(program) = program {
+                match &program[0] {
+                    Statement::Expression { value, .. } => {
+                        if value.to_string() != expected {
+                            panic!("Expected value to be {}, got {}", expected, value);
+                        }
+                    }
+                    stmt => {
+                        panic!("Expected statement to be expression, got {:?}", stmt);
+                    }
+                }
+            } else {
+                panic!("Parse program returned None");
+            }
+        }
+    }
+
+    #[test]
+    fn test_null_operators() {
+        let tests = vec![
+            ("a?.b?.c", "((a?.b)?.c)"),
+            ("a?.b.c", "((a?.b).c)"),
+            ("a?.f(1)", "(a?.f)(1)"),
+            ("a ?? b ?? c", "((a ?? b) ?? c)"),
+            ("a == b ?? c", "((a == b) ?? c)"),
+            ("a.b is null", "((a.b) is null)"),
+            ("x ?? null", "(x ?? null)"),
+            ("x is str == true", "((x is str) == true)"),
+            ("str(x) + bool(y)", "(str(x) + bool(y))"),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut p = Parser::new(tokens.expect("Could not tokenize"));
+            let program = p.parse_program();
+            if let Some(program) = program {
+                match &program[0] {
+                    Statement::Expression { value, .. } => {
+                        if value.to_string() != expected {
+                            panic!("Expected value to be {}, got {}", expected, value);
+                        }
+                    }
+                    stmt => {
+                        panic!("Expected statement to be expression, got {:?}", stmt);
+                    }
+                }
+            } else {
+                panic!("Parse program returned None");
+            }
+        }
+    }
+
+    #[test]
+    fn test_logical_operators() {
+        let tests = vec![
+            ("a or b and c", "(a or (b and c))"),
+            ("a and b or c", "((a and b) or c)"),
+            ("not a == b and c", "((not (a == b)) and c)"),
+            ("not a ?? b", "(not (a ?? b))"),
+            ("!a and b", "((!a) and b)"),
+            ("a ?? b or c", "((a ?? b) or c)"),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut p = Parser::new(tokens.expect("Could not tokenize"));
+            let program = p.parse_program();
+            if let Some(program) = program {
+                match &program[0] {
+                    Statement::Expression { value, .. } => {
+                        if value.to_string() != expected {
+                            panic!("Expected value to be {}, got {}", expected, value);
+                        }
+                    }
+                    stmt => {
+                        panic!("Expected statement to be expression, got {:?}", stmt);
+                    }
+                }
+            } else {
+                panic!("Parse program returned None");
+            }
+        }
+    }
+
+    #[test]
+    fn test_match_and_patterns() {
+        let tests = vec![
+            (
+                "match x do 0 -> a\n1 to 9 -> b end",
+                "match x do 0 -> a, 1 to 9 -> b, end",
+            ),
+            (
+                "match x do [a, _, ...rest] if a > 1 -> rest, -1.5 -> 'neg' end",
+                "match x do [a, _, ...rest] if (a > 1) -> rest, -1.5 -> neg, end",
+            ),
+            (
+                r#"match r do {"code": 0, out} -> out; null -> 1 end"#,
+                r#"match r do {"code": 0, "out": out} -> out, null -> 1, end"#,
+            ),
+            ("let [a, ...b] = xs", "let [a, ...b] = xs"),
+            (
+                r#"const {name} = person"#,
+                r#"const {"name": name} = person"#,
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut p = Parser::new(tokens.expect("Could not tokenize"));
+            let program = p.parse_program();
+            if let Some(program) = program {
+                let got = match &program[0] {
+                    Statement::Expression { value, .. } => value.to_string(),
+                    stmt => format!("{:?}", stmt),
+                };
+                if got != expected {
+                    panic!("Expected {}, got {}", expected, got);
+                }
+            } else {
+                panic!("Parse program returned None");
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let tests = vec![
+            (
+                "let ok = x is int;",
+                "Parse error at line 1: expected a type after `is`, got `int`",
+            ),
+            (
+                "println(x is foo)",
+                "Parse error at line 1: expected a type after `is`, got `foo`",
+            ),
+            (
+                "let [...r, x] = xs",
+                "Parse error at line 1: invalid pattern",
+            ),
+            (
+                "let n = match xs do [...r, x] -> x end",
+                "Parse error at line 1: invalid pattern",
+            ),
+            (
+                "let x = match 1 do 1 -> 2",
+                "Parse error at line 1: expected `end` to close match",
+            ),
+            (
+                "let x = match 1 do 1 -> 2, 3 -> 4",
+                "Parse error at line 1: expected `end` to close match",
+            ),
+            (
+                "let x = match 1 do 1 2 end",
+                "Parse error at line 1: expected `->` after match pattern",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut p = Parser::new(tokens.expect("Could not tokenize"));
+            if let Some(program) = p.parse_program() {
+                panic!("Expected a parse error for {}, got {:?}", input, program);
+            }
+            assert_eq!(p.errors, vec![expected.to_string()]);
+        }
+    }
+
+    #[test]
+    fn test_assign_statement() {
+        let input = String::from(r#"cfg.server["port"] = 8000 + 80; xs[0] == 1"#);
+
+        let mut l = Lexer::new(input);
+        let tokens = l.gen_tokens();
+
+        let mut p = Parser::new(tokens.expect("Could not tokenize"));
+        let program = p.parse_program();
+        if let Some(program) = program {
+            if program.len() != 2 {
+                panic!(
+                    "Program does not contain 2 statements, got {}",
+                    program.len()
+                );
+            }
+
+            match &program[0] {
+                Statement::Assign { target, value, .. } => {
+                    if target.to_string() != "((cfg.server)[port])" {
+                        panic!("Expected target to be ((cfg.server)[port]), got {}", target);
+                    }
+                    if value.to_string() != "(8000 + 80)" {
+                        panic!("Expected value to be (8000 + 80), got {}", value);
+                    }
+                }
+                stmt => {
+                    panic!("Expected statement to be assignment, got {:?}", stmt);
+                }
+            }
+
+            match &program[1] {
+                Statement::Expression { value, .. } => {
+                    if value.to_string() != "((xs[0]) == 1)" {
+                        panic!("Expected value to be ((xs[0]) == 1), got {}", value);
+                    }
+                }
+                stmt => {
+                    panic!("Expected statement to be expression, got {:?}", stmt);
+                }
+            }
+        } else {
+            panic!("Parse program returned None");
+        }
+    }
+
     #[test]
     fn test_array_literal() {
         let input = String::from("[1, 2 * 2, 3 + 3]");
@@ -273,6 +511,40 @@ mod parser_tests {
         }
     }
 
+    #[test]
+    fn fn_literal_with_type_hints() {
+        let tests = vec![
+            (
+                "function(str name, number n) -> str { name }",
+                "fn(str name, number n) -> str {[name]}",
+            ),
+            ("function(arr xs, f) { f(xs) }", "fn(arr xs, f) {[f(xs)]}"),
+            ("function() -> bool { true }", "fn() -> bool {[true]}"),
+        ];
+
+        for (input, expected) in tests {
+            let mut l = Lexer::new(input.to_string());
+            let tokens = l.gen_tokens();
+
+            let mut p = Parser::new(tokens.expect("Could not tokenize"));
+            let program = p.parse_program();
+            if let Some(program) = program {
+                match &program[0] {
+                    Statement::Expression { value, .. } => {
+                        if value.to_string() != expected {
+                            panic!("Expected value to be {}, got {}", expected, value);
+                        }
+                    }
+                    stmt => {
+                        panic!("Expected statement to be expression, got {:?}", stmt);
+                    }
+                }
+            } else {
+                panic!("Parse program returned None");
+            }
+        }
+    }
+
     #[test]
     fn if_statement() {
         let input = String::from(
@@ -583,7 +855,7 @@ mod parser_tests {
                 );
             }
 
-            let tests = vec!["x", "y", "foobar"];
+            let tests = ["x", "y", "foobar"];
 
             for (i, tt) in tests.iter().enumerate() {
                 let stmt = &program[i];